@@ -0,0 +1,268 @@
+use std::sync::LazyLock;
+
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::DateTime;
+use chrono_tz::Tz;
+use regex::{Captures, Regex};
+use reqwest::Url;
+
+use crate::{
+    catchup_type, source_type, sql,
+    types::{CatchupUrl, Channel},
+    xtream,
+};
+
+static CATCHUP_PLACEHOLDER_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\$?\{(?P<name>[A-Za-z]+)(?::(?P<arg>[^}]*))?\}"#).unwrap());
+
+pub fn get_catchup_url(channel_id: i64, start: i64, duration_minutes: u32) -> Result<CatchupUrl> {
+    let channel = sql::get_channel_by_id(channel_id)?;
+    if channel.tv_archive != Some(true) {
+        bail!("Channel has no catchup archive");
+    }
+    if duration_minutes == 0 {
+        bail!("Catchup duration must be greater than zero");
+    }
+    let now = chrono::Utc::now().timestamp();
+    if start >= now {
+        bail!("Catchup start time must be in the past");
+    }
+    if let Some(days) = channel.tv_archive_duration.filter(|days| *days > 0)
+        && start < now - days * 86400
+    {
+        bail!("Catchup start time is outside the {days} day archive window");
+    }
+    let source = sql::get_source_from_id(channel.source_id.context("no source id")?)?;
+    match source.source_type {
+        source_type::XTREAM => xtream::get_catchup_url(
+            source,
+            channel.stream_id.context("channel has no stream id")?,
+            start,
+            duration_minutes,
+        ),
+        _ => {
+            // XC panels expect local times; without a stored timezone they are sent in UTC
+            let timezone = match channel.catchup_type.as_deref() {
+                Some(catchup_type::XC) => {
+                    sql::get_source_timezone(source.id.context("no source id")?)?
+                }
+                _ => None,
+            };
+            let end = start + duration_minutes as i64 * 60;
+            get_m3u_catchup_url(&channel, start, end, now, timezone.as_deref())
+        }
+    }
+}
+
+fn get_m3u_catchup_url(
+    channel: &Channel,
+    start: i64,
+    end: i64,
+    now: i64,
+    timezone: Option<&str>,
+) -> Result<CatchupUrl> {
+    let url = channel.url.as_deref().context("channel has no url")?;
+    let template = match (channel.catchup_type.as_deref(), &channel.catchup_source) {
+        (Some(catchup_type::APPEND), Some(source)) => format!("{url}{source}"),
+        (Some(catchup_type::SHIFT | catchup_type::TIMESHIFT), _) => {
+            format!("{url}{}utc={{utc}}&lutc={{lutc}}", get_query_separator(url))
+        }
+        (Some(catchup_type::FLUSSONIC | catchup_type::FLUSSONIC_SHORT), _) => {
+            get_flussonic_template(url)?
+        }
+        (Some(catchup_type::XC), _) => get_xc_template(url)?,
+        (_, Some(source)) if source.starts_with('?') || source.starts_with('&') => {
+            format!("{url}{source}")
+        }
+        (_, Some(source)) => source.clone(),
+        _ => bail!("Channel has no catchup source"),
+    };
+    Ok(CatchupUrl {
+        url: expand_catchup_template(&template, start, end, now, xtream::parse_timezone(timezone)),
+        fallback_url: None,
+    })
+}
+
+pub fn is_supported_catchup(catchup_type: Option<&str>, catchup_source: Option<&str>) -> bool {
+    catchup_source.is_some()
+        || matches!(
+            catchup_type,
+            Some(
+                catchup_type::SHIFT
+                    | catchup_type::TIMESHIFT
+                    | catchup_type::FLUSSONIC
+                    | catchup_type::FLUSSONIC_SHORT
+                    | catchup_type::XC
+            )
+        )
+}
+
+fn get_query_separator(url: &str) -> char {
+    match url.contains('?') {
+        true => '&',
+        false => '?',
+    }
+}
+
+fn get_flussonic_template(url: &str) -> Result<String> {
+    let mut url = Url::parse(url)?;
+    let file = url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .context("Invalid flussonic url")?
+        .to_string();
+    let file = match file.strip_suffix(".m3u8") {
+        Some(stem) => format!("{stem}-{{utc}}-{{duration}}.m3u8"),
+        None => "timeshift_abs-{utc}.ts".to_string(),
+    };
+    url.path_segments_mut()
+        .map_err(|_| anyhow!("Invalid flussonic url"))?
+        .pop()
+        .push(&file);
+    Ok(decode_template_braces(url.as_str()))
+}
+
+fn get_xc_template(url: &str) -> Result<String> {
+    let mut url = Url::parse(url)?;
+    let segments: Vec<String> = url
+        .path_segments()
+        .context("Invalid xc url")?
+        .map(|segment| segment.to_string())
+        .collect();
+    let [.., username, password, file] = segments.as_slice() else {
+        bail!("Invalid xc url");
+    };
+    url.set_path(&format!(
+        "timeshift/{username}/{password}/{{duration:60}}/{{Y}}-{{m}}-{{d}}:{{H}}-{{M}}/{file}"
+    ));
+    Ok(decode_template_braces(url.as_str()))
+}
+
+fn decode_template_braces(url: &str) -> String {
+    url.replace("%7B", "{").replace("%7D", "}")
+}
+
+fn expand_catchup_template(
+    template: &str,
+    start: i64,
+    end: i64,
+    now: i64,
+    timezone: Option<Tz>,
+) -> String {
+    CATCHUP_PLACEHOLDER_REGEX
+        .replace_all(template, |caps: &Captures| {
+            let arg = caps.name("arg").map(|arg| arg.as_str());
+            let divisor = || arg.and_then(|arg| arg.parse().ok()).unwrap_or(1).max(1);
+            let timestamp = |value: i64| match arg {
+                Some(format) => format_catchup_time(value, format, timezone),
+                None => value.to_string(),
+            };
+            match &caps["name"] {
+                "utc" | "start" => timestamp(start),
+                "utcend" | "end" => timestamp(end),
+                "lutc" | "now" | "timestamp" => timestamp(now),
+                "duration" => ((end - start) / divisor()).to_string(),
+                "offset" => ((now - start) / divisor()).to_string(),
+                name @ ("Y" | "m" | "d" | "H" | "M" | "S") => {
+                    format_catchup_time(start, name, timezone)
+                }
+                _ => caps[0].to_string(),
+            }
+        })
+        .to_string()
+}
+
+fn format_catchup_time(timestamp: i64, format: &str, timezone: Option<Tz>) -> String {
+    let format: String = format
+        .chars()
+        .map(|c| match c {
+            'Y' | 'm' | 'd' | 'H' | 'M' | 'S' => format!("%{c}"),
+            '%' => "%%".to_string(),
+            c => c.to_string(),
+        })
+        .collect();
+    let Some(date) = DateTime::from_timestamp(timestamp, 0) else {
+        return String::new();
+    };
+    match timezone {
+        Some(timezone) => date.with_timezone(&timezone).format(&format).to_string(),
+        None => date.format(&format).to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test_catchup {
+    use crate::catchup::{
+        expand_catchup_template, get_flussonic_template, get_xc_template, is_supported_catchup,
+    };
+
+    #[test]
+    fn test_expand_catchup_template() {
+        let start = 1792346400;
+        let end = start + 3600;
+        let now = start + 7200;
+        assert_eq!(
+            expand_catchup_template(
+                "http://a.local/1.ts?utc={utc}&lutc={lutc}&d={duration}&t=${timestamp}",
+                start,
+                end,
+                now,
+                None
+            ),
+            "http://a.local/1.ts?utc=1792346400&lutc=1792353600&d=3600&t=1792353600"
+        );
+        assert_eq!(
+            expand_catchup_template(
+                "http://a.local/{Y}-{m}-{d}/{H}{M}{S}/{duration:60}/{offset:60}/{end:YmdHMS}",
+                start,
+                end,
+                now,
+                None
+            ),
+            "http://a.local/2026-10-18/180000/60/120/20261018190000"
+        );
+        assert_eq!(
+            expand_catchup_template("{unknown}", start, end, now, None),
+            "{unknown}"
+        );
+        assert_eq!(
+            expand_catchup_template(
+                "http://a.local/timeshift/u/p/{duration:60}/{Y}-{m}-{d}:{H}-{M}/1.ts",
+                start,
+                end,
+                now,
+                "America/Toronto".parse().ok()
+            ),
+            "http://a.local/timeshift/u/p/60/2026-10-18:14-00/1.ts"
+        );
+    }
+
+    #[test]
+    fn test_is_supported_catchup() {
+        assert!(is_supported_catchup(Some("shift"), None));
+        assert!(is_supported_catchup(Some("flussonic"), None));
+        assert!(is_supported_catchup(Some("xc"), None));
+        assert!(is_supported_catchup(Some("append"), Some("?utc={utc}")));
+        assert!(is_supported_catchup(None, Some("http://a.local/{utc}.ts")));
+        assert!(!is_supported_catchup(Some("append"), None));
+        assert!(!is_supported_catchup(Some("default"), None));
+        assert!(!is_supported_catchup(Some("unknown"), None));
+        assert!(!is_supported_catchup(None, None));
+    }
+
+    #[test]
+    fn test_catchup_templates() {
+        assert_eq!(
+            get_flussonic_template("http://a.local/ch1/index.m3u8?token=x").unwrap(),
+            "http://a.local/ch1/index-{utc}-{duration}.m3u8?token=x"
+        );
+        assert_eq!(
+            get_flussonic_template("http://a.local/ch1/mpegts").unwrap(),
+            "http://a.local/ch1/timeshift_abs-{utc}.ts"
+        );
+        assert_eq!(
+            get_xc_template("http://a.local/live/user/pass/42.ts").unwrap(),
+            "http://a.local/timeshift/user/pass/{duration:60}/{Y}-{m}-{d}:{H}-{M}/42.ts"
+        );
+    }
+}
@@ -0,0 +1,6 @@
+pub const APPEND: &str = "append";
+pub const SHIFT: &str = "shift";
+pub const TIMESHIFT: &str = "timeshift";
+pub const FLUSSONIC: &str = "flussonic";
+pub const FLUSSONIC_SHORT: &str = "fs";
+pub const XC: &str = "xc";
@@ -0,0 +1,457 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, NaiveDateTime};
+use flate2::read::MultiGzDecoder;
+use quick_xml::Reader;
+use quick_xml::events::{BytesStart, Event};
+use regex::Regex;
+use rusqlite::Transaction;
+use unicode_normalization::{UnicodeNormalization, char::is_combining_mark};
+
+use crate::{
+    epg_match_type, settings, source_type, sql,
+    types::{EpgChannel, EpgMatch, Programme, Reminder, Source},
+    utils, xtream,
+};
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const XMLTV_TIME_FORMAT: &str = "%Y%m%d%H%M%S";
+const XMLTV_TIME_FORMAT_OFFSET: &str = "%Y%m%d%H%M%S %z";
+const ID_MATCH_CONFIDENCE: u8 = 100;
+const ID_CASE_INSENSITIVE_MATCH_CONFIDENCE: u8 = 90;
+const NAME_MATCH_CONFIDENCE: u8 = 75;
+pub const STREAM_MATCH_CONFIDENCE: u8 = 100;
+const DEFAULT_EPG_RETENTION_DAYS: u16 = 1;
+
+const COUNTRY_PREFIXES: &[&str] = &[
+    "ad", "ae", "af", "al", "am", "ar", "arb", "at", "au", "az", "ba", "bd", "be", "bg", "bh",
+    "br", "by", "ca", "ch", "cl", "cn", "co", "cr", "cy", "cz", "de", "deu", "dk", "do", "dz",
+    "ec", "ee", "eg", "en", "eng", "es", "esp", "eu", "ex", "fi", "fr", "fra", "gb", "ge", "ger",
+    "gr", "hk", "hr", "hu", "id", "ie", "il", "in", "int", "iq", "ir", "is", "it", "ita", "jo",
+    "jp", "ke", "kr", "kw", "kz", "la", "lat", "lb", "lt", "lu", "lv", "ly", "ma", "md", "me",
+    "mk", "mt", "mx", "my", "ng", "nl", "no", "nz", "om", "pe", "ph", "pk", "pl", "pol", "por",
+    "pt", "py", "qa", "ro", "rs", "ru", "rus", "sa", "se", "sg", "si", "sk", "sn", "spa", "sy",
+    "th", "tn", "tr", "tur", "tw", "ua", "ug", "uk", "us", "usa", "uy", "ve", "vn", "ye", "yu",
+    "za",
+];
+
+static COUNTRY_PREFIX_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    let codes = COUNTRY_PREFIXES.join("|");
+    Regex::new(&format!(
+        r#"^\s*(?:[|\[(]\s*(?:{codes})\s*[|\])]|(?:{codes})\s*[:|])\s*"#
+    ))
+    .unwrap()
+});
+static QUALITY_SUFFIX_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"\b(?:hd|fhd|uhd|sd|4k|8k|hevc|h\.?26[45]|\d{3,4}[pi]|\d{2}fps)\b"#).unwrap()
+});
+
+#[derive(Default)]
+struct XmltvProcessing {
+    channels: HashMap<String, i64>,
+    channel: Option<EpgChannel>,
+    programme: Option<Programme>,
+    programme_channel: Option<String>,
+    text: String,
+    source_id: i64,
+    shift: i64,
+    cutoff: i64,
+}
+
+pub async fn import_epg(source_id: i64, url: String) -> Result<()> {
+    let source = sql::get_source_from_id(source_id)?;
+    let path = match url.starts_with("http://") || url.starts_with("https://") {
+        true => download_xmltv(&source, &url).await?,
+        false => url,
+    };
+    tokio::task::spawn_blocking(move || read_xmltv(source_id, &path)).await??;
+    Ok(())
+}
+
+pub async fn refresh_epg(source_id: i64) -> Result<()> {
+    let source = sql::get_source_from_id(source_id)?;
+    let mut urls = Vec::new();
+    if source.source_type == source_type::XTREAM {
+        urls.push(xtream::get_xmltv_url(source)?);
+    }
+    urls.extend(sql::get_source_epg_urls(source_id)?);
+    for url in urls {
+        import_epg(source_id, url.clone())
+            .await
+            .with_context(|| format!("Failed to import epg from {url}"))
+            .unwrap_or_else(|e| tracing::error!("{:?}", e));
+    }
+    sql::purge_programmes(get_retention_cutoff()?)
+}
+
+pub fn get_retention_cutoff() -> Result<i64> {
+    let days = settings::get_settings()?
+        .epg_retention_days
+        .unwrap_or(DEFAULT_EPG_RETENTION_DAYS);
+    Ok(chrono::Utc::now().timestamp() - days as i64 * 86400)
+}
+
+pub fn get_epg_shift(source_id: i64) -> Result<i64> {
+    Ok((sql::get_source_epg_shift(source_id)? * 3600.0).round() as i64)
+}
+
+async fn download_xmltv(source: &Source, url: &str) -> Result<String> {
+    let client = utils::get_http_client(source)?;
+    let mut response = client.get(url).send().await?;
+    if !response.status().is_success() {
+        bail!(
+            "Failed to get xmltv from link, status: {}",
+            response.status()
+        );
+    }
+    let path = get_tmp_path(source.id.context("no source id")?)?;
+    let mut file = File::create(&path)?;
+    while let Some(chunk) = response.chunk().await? {
+        file.write_all(&chunk)?;
+    }
+    Ok(path)
+}
+
+fn get_tmp_path(source_id: i64) -> Result<String> {
+    let mut path = PathBuf::from_str(utils::TEMP_PATH.get().context("no temp path")?)?;
+    if !path.exists() {
+        std::fs::create_dir_all(&path)?;
+    }
+    path.push(format!("epg_{source_id}.xml"));
+    Ok(path.to_string_lossy().to_string())
+}
+
+pub fn read_xmltv(source_id: i64, path: &str) -> Result<()> {
+    let file = File::open(path).context("Failed to open xmltv file")?;
+    let mut reader = BufReader::new(file);
+    let reader: Box<dyn BufRead> = match reader.fill_buf()?.starts_with(&GZIP_MAGIC) {
+        true => Box::new(BufReader::new(MultiGzDecoder::new(reader))),
+        false => Box::new(reader),
+    };
+    let shift = get_epg_shift(source_id)?;
+    let cutoff = get_retention_cutoff()?;
+    sql::do_tx(|tx| {
+        parse_xmltv(reader, source_id, shift, cutoff, tx)?;
+        match_channels(tx, source_id)?;
+        sql::analyze(tx)
+    })
+}
+
+pub fn match_channels(tx: &Transaction, source_id: i64) -> Result<()> {
+    sql::reset_epg_matches(tx, source_id)?;
+    sql::apply_epg_overrides(tx, source_id)?;
+    sql::link_epg_channels(tx, source_id, ID_MATCH_CONFIDENCE)?;
+    let mut ids: HashMap<String, i64> = HashMap::new();
+    let mut names: HashMap<String, i64> = HashMap::new();
+    for epg_channel in sql::get_epg_channels_by_source(tx, source_id)? {
+        let Some(id) = epg_channel.id else {
+            continue;
+        };
+        ids.entry(epg_channel.xmltv_id.to_lowercase()).or_insert(id);
+        if let Some(name) = epg_channel
+            .display_name
+            .as_deref()
+            .map(normalize_channel_name)
+            .filter(|name| !name.is_empty())
+        {
+            names.entry(name).or_insert(id);
+        }
+    }
+    if ids.is_empty() {
+        return Ok(());
+    }
+    for channel in sql::get_unmatched_channels(tx, source_id)? {
+        let Some(channel_id) = channel.id else {
+            continue;
+        };
+        let by_id = channel
+            .tvg_id
+            .and_then(|tvg_id| ids.get(&tvg_id.to_lowercase()))
+            .map(|id| {
+                (
+                    *id,
+                    epg_match_type::ID,
+                    ID_CASE_INSENSITIVE_MATCH_CONFIDENCE,
+                )
+            });
+        let by_name = || {
+            names
+                .get(&normalize_channel_name(&channel.name))
+                .map(|id| (*id, epg_match_type::NAME, NAME_MATCH_CONFIDENCE))
+        };
+        if let Some((epg_channel_id, match_type, confidence)) = by_id.or_else(by_name) {
+            sql::set_epg_match(tx, channel_id, epg_channel_id, match_type, confidence)?;
+        }
+    }
+    Ok(())
+}
+
+fn normalize_channel_name(name: &str) -> String {
+    let mut name: String = name
+        .to_lowercase()
+        .nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .collect();
+    while let Some(prefix) = COUNTRY_PREFIX_REGEX.find(&name) {
+        if prefix.end() == name.len() {
+            break;
+        }
+        name = name[prefix.end()..].to_string();
+    }
+    QUALITY_SUFFIX_REGEX
+        .replace_all(&name, "")
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == '+')
+        .collect()
+}
+
+pub fn get_epg_match(channel_id: i64) -> Result<EpgMatch> {
+    sql::get_epg_match(channel_id)
+}
+
+pub fn set_epg_override(channel_id: i64, xmltv_id: Option<String>) -> Result<()> {
+    let channel = sql::get_channel_by_id(channel_id)?;
+    let source_id = channel.source_id.context("no source id")?;
+    sql::do_tx(|tx| {
+        sql::set_epg_override(tx, source_id, &channel.name, xmltv_id)?;
+        match_channels(tx, source_id)
+    })
+}
+
+pub fn delete_epg_override(channel_id: i64) -> Result<()> {
+    let channel = sql::get_channel_by_id(channel_id)?;
+    let source_id = channel.source_id.context("no source id")?;
+    sql::do_tx(|tx| {
+        sql::delete_epg_override(tx, source_id, &channel.name)?;
+        match_channels(tx, source_id)
+    })
+}
+
+pub fn add_reminder(channel_id: i64, start: i64) -> Result<i64> {
+    let channel = sql::get_channel_by_id(channel_id)?;
+    let programme = sql::get_channel_programme_at(channel_id, start)?
+        .context("No programme found at this time")?;
+    if programme.start <= chrono::Utc::now().timestamp() {
+        bail!("Programme has already started");
+    }
+    sql::insert_reminder(&Reminder {
+        channel_id: Some(channel_id),
+        source_id: channel.source_id.context("no source id")?,
+        channel_name: channel.name,
+        title: programme.title,
+        start: programme.start,
+        end: programme.end,
+        ..Default::default()
+    })
+}
+
+pub fn get_upcoming_reminders(minutes: u32) -> Result<Vec<Reminder>> {
+    let now = chrono::Utc::now().timestamp();
+    sql::get_upcoming_reminders(now, now + minutes as i64 * 60)
+}
+
+fn parse_xmltv(
+    reader: impl BufRead,
+    source_id: i64,
+    shift: i64,
+    cutoff: i64,
+    tx: &Transaction,
+) -> Result<()> {
+    let mut reader = Reader::from_reader(reader);
+    reader.config_mut().trim_text(true);
+    let mut buf = Vec::new();
+    let mut processing = XmltvProcessing {
+        source_id,
+        shift,
+        cutoff,
+        ..Default::default()
+    };
+    loop {
+        let event = reader
+            .read_event_into(&mut buf)
+            .with_context(|| format!("Failed to read xmltv at {}", reader.buffer_position()))?;
+        match event {
+            Event::Start(e) => on_start(&mut processing, &e),
+            Event::Empty(e) => {
+                on_start(&mut processing, &e);
+                on_end(&mut processing, e.local_name().as_ref(), tx);
+            }
+            Event::Text(e) => match e.unescape() {
+                Ok(text) => processing.text.push_str(&text),
+                Err(e) => tracing::error!("Failed to unescape xmltv text: {:?}", e),
+            },
+            Event::CData(e) => processing
+                .text
+                .push_str(&String::from_utf8_lossy(&e.into_inner())),
+            Event::End(e) => on_end(&mut processing, e.local_name().as_ref(), tx),
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+    Ok(())
+}
+
+fn on_start(processing: &mut XmltvProcessing, e: &BytesStart) {
+    match e.local_name().as_ref() {
+        b"channel" => {
+            processing.channel = get_attribute(e, "id").map(|xmltv_id| EpgChannel {
+                source_id: processing.source_id,
+                xmltv_id,
+                ..Default::default()
+            });
+        }
+        b"programme" => {
+            processing.programme_channel = get_attribute(e, "channel");
+            processing.programme = get_attribute(e, "start")
+                .and_then(|start| parse_xmltv_time(&start))
+                .map(|start| Programme {
+                    start: start + processing.shift,
+                    end: get_attribute(e, "stop")
+                        .and_then(|stop| parse_xmltv_time(&stop))
+                        .unwrap_or(start)
+                        + processing.shift,
+                    ..Default::default()
+                })
+                .filter(|programme| programme.end >= processing.cutoff);
+        }
+        b"icon" => {
+            let icon = get_attribute(e, "src");
+            if let Some(programme) = processing.programme.as_mut() {
+                programme.icon = programme.icon.take().or(icon);
+            } else if let Some(channel) = processing.channel.as_mut() {
+                channel.icon = channel.icon.take().or(icon);
+            }
+        }
+        _ => processing.text.clear(),
+    }
+}
+
+fn on_end(processing: &mut XmltvProcessing, name: &[u8], tx: &Transaction) {
+    let text = std::mem::take(&mut processing.text);
+    let text = Some(text.trim().to_string()).filter(|t| !t.is_empty());
+    match name {
+        b"display-name" => {
+            if let Some(channel) = processing.channel.as_mut() {
+                channel.display_name = channel.display_name.take().or(text);
+            }
+        }
+        b"title" => {
+            if let Some(programme) = processing.programme.as_mut()
+                && programme.title.is_empty()
+            {
+                programme.title = text.unwrap_or_default();
+            }
+        }
+        b"desc" => {
+            if let Some(programme) = processing.programme.as_mut() {
+                programme.description = programme.description.take().or(text);
+            }
+        }
+        b"category" => {
+            if let Some(programme) = processing.programme.as_mut() {
+                programme.category = programme.category.take().or(text);
+            }
+        }
+        b"channel" => {
+            if let Some(channel) = processing.channel.take()
+                && let Err(e) = commit_epg_channel(processing, channel, tx)
+            {
+                tracing::error!("{:?}", e);
+            }
+        }
+        b"programme" => {
+            if let Some(programme) = processing.programme.take() {
+                commit_programme(processing, programme, tx)
+                    .unwrap_or_else(|e| tracing::error!("{:?}", e));
+            }
+        }
+        _ => {}
+    }
+}
+
+fn commit_epg_channel(
+    processing: &mut XmltvProcessing,
+    channel: EpgChannel,
+    tx: &Transaction,
+) -> Result<i64> {
+    let id = sql::insert_epg_channel(tx, &channel)
+        .with_context(|| format!("Failed to insert epg channel {}", channel.xmltv_id))?;
+    processing.channels.insert(channel.xmltv_id, id);
+    Ok(id)
+}
+
+fn commit_programme(
+    processing: &mut XmltvProcessing,
+    mut programme: Programme,
+    tx: &Transaction,
+) -> Result<()> {
+    let xmltv_id = processing
+        .programme_channel
+        .take()
+        .context("programme has no channel")?;
+    let epg_channel_id = match processing.channels.get(&xmltv_id) {
+        Some(id) => *id,
+        None => {
+            let channel = EpgChannel {
+                source_id: processing.source_id,
+                xmltv_id,
+                ..Default::default()
+            };
+            commit_epg_channel(processing, channel, tx)?
+        }
+    };
+    programme.epg_channel_id = Some(epg_channel_id);
+    sql::insert_programme(tx, &programme)
+        .with_context(|| format!("Failed to insert programme {}", programme.title))
+}
+
+fn get_attribute(e: &BytesStart, name: &str) -> Option<String> {
+    e.try_get_attribute(name)
+        .ok()
+        .flatten()
+        .and_then(|a| a.unescape_value().ok().map(|v| v.trim().to_string()))
+        .filter(|v| !v.is_empty())
+}
+
+fn parse_xmltv_time(value: &str) -> Option<i64> {
+    let value = value.trim();
+    DateTime::parse_from_str(value, XMLTV_TIME_FORMAT_OFFSET)
+        .map(|d| d.timestamp())
+        .ok()
+        .or_else(|| {
+            NaiveDateTime::parse_from_str(value.get(..14)?, XMLTV_TIME_FORMAT)
+                .map(|d| d.and_utc().timestamp())
+                .ok()
+        })
+}
+
+#[cfg(test)]
+mod test_epg {
+    use crate::epg::{normalize_channel_name, parse_xmltv_time};
+
+    #[test]
+    fn test_parse_xmltv_time() {
+        assert_eq!(parse_xmltv_time("20261018200000 +0200"), Some(1792346400));
+        assert_eq!(parse_xmltv_time("20261018180000 +0000"), Some(1792346400));
+        assert_eq!(parse_xmltv_time("20261018180000"), Some(1792346400));
+        assert_eq!(parse_xmltv_time("not a date"), None);
+    }
+
+    #[test]
+    fn test_normalize_channel_name() {
+        assert_eq!(normalize_channel_name("FR: TF1 HD"), "tf1");
+        assert_eq!(normalize_channel_name("|EU| France 2 FHD"), "france2");
+        assert_eq!(normalize_channel_name("[UK] BBC One +1"), "bbcone+1");
+        assert_eq!(normalize_channel_name("Télé-Québec 1080p"), "telequebec");
+        assert_eq!(normalize_channel_name("ARTE"), "arte");
+        assert_eq!(normalize_channel_name("BBC: One"), "bbcone");
+        assert_eq!(normalize_channel_name("[SKY] Cinema"), "skycinema");
+    }
+}
@@ -0,0 +1,4 @@
+pub const ID: u8 = 0;
+pub const NAME: u8 = 1;
+pub const MANUAL: u8 = 2;
+pub const STREAM: u8 = 3;
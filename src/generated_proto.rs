@@ -30,13 +30,46 @@ pub struct Channel {
     pub season_id: ::core::option::Option<i64>,
     #[prost(int64, optional, tag = "14")]
     pub episode_num: ::core::option::Option<i64>,
+    #[prost(message, optional, tag = "15")]
+    pub epg_now: ::core::option::Option<Programme>,
+    #[prost(message, optional, tag = "16")]
+    pub epg_next: ::core::option::Option<Programme>,
+    #[prost(int64, optional, tag = "17")]
+    pub tv_archive_duration: ::core::option::Option<i64>,
+    #[prost(int64, optional, tag = "18")]
+    pub channel_number: ::core::option::Option<i64>,
+    #[prost(int64, optional, tag = "19")]
+    pub duration: ::core::option::Option<i64>,
+    #[prost(string, optional, tag = "20")]
+    pub language: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "21")]
+    pub country: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct Programme {
+    #[prost(int64, optional, tag = "1")]
+    pub id: ::core::option::Option<i64>,
+    #[prost(int64, optional, tag = "2")]
+    pub epg_channel_id: ::core::option::Option<i64>,
+    #[prost(string, tag = "3")]
+    pub title: ::prost::alloc::string::String,
+    #[prost(string, optional, tag = "4")]
+    pub description: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "5")]
+    pub category: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "6")]
+    pub icon: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(int64, tag = "7")]
+    pub start: i64,
+    #[prost(int64, tag = "8")]
+    pub end: i64,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ChannelList {
     #[prost(message, repeated, tag = "1")]
     pub channels: ::prost::alloc::vec::Vec<Channel>,
 }
-#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Source {
     #[prost(int64, optional, tag = "1")]
     pub id: ::core::option::Option<i64>,
@@ -60,6 +93,14 @@ pub struct Source {
     pub stream_user_agent: ::core::option::Option<::prost::alloc::string::String>,
     #[prost(int64, optional, tag = "13")]
     pub last_updated: ::core::option::Option<i64>,
+    #[prost(double, optional, tag = "14")]
+    pub epg_shift: ::core::option::Option<f64>,
+    #[prost(bool, tag = "15")]
+    pub ignore_ssl: bool,
+    #[prost(string, optional, tag = "16")]
+    pub encoding: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(bool, tag = "17")]
+    pub expand_playlists: bool,
 }
 #[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct Settings {
@@ -79,6 +120,8 @@ pub struct Settings {
     pub show_movies: ::core::option::Option<bool>,
     #[prost(bool, optional, tag = "8")]
     pub show_series: ::core::option::Option<bool>,
+    #[prost(uint32, optional, tag = "9")]
+    pub epg_retention_days: ::core::option::Option<u32>,
 }
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct Filters {
@@ -102,6 +145,8 @@ pub struct Filters {
     pub sort: u32,
     #[prost(int64, optional, tag = "10")]
     pub season: ::core::option::Option<i64>,
+    #[prost(bool, tag = "11")]
+    pub search_programmes: bool,
 }
 #[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct ToggleFavorite {
@@ -158,7 +203,7 @@ pub struct GetEpisodes {
     #[prost(string, optional, tag = "3")]
     pub fallback_image: ::core::option::Option<::prost::alloc::string::String>,
 }
-#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ChannelHttpHeaders {
     #[prost(int64, optional, tag = "1")]
     pub id: ::core::option::Option<i64>,
@@ -172,6 +217,13 @@ pub struct ChannelHttpHeaders {
     pub http_origin: ::core::option::Option<::prost::alloc::string::String>,
     #[prost(bool, optional, tag = "6")]
     pub ignore_ssl: ::core::option::Option<bool>,
+    #[prost(string, optional, tag = "7")]
+    pub cookie: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(map = "string, string", tag = "8")]
+    pub extra_headers: ::std::collections::HashMap<
+        ::prost::alloc::string::String,
+        ::prost::alloc::string::String,
+    >,
 }
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct GetEnabledSourcesMinimal {
@@ -190,13 +242,189 @@ pub struct SetSourceEnabled {
     #[prost(bool, tag = "2")]
     pub enabled: bool,
 }
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct ImportEpg {
+    #[prost(int64, tag = "1")]
+    pub source_id: i64,
+    #[prost(string, tag = "2")]
+    pub url: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct EpgGridQuery {
+    #[prost(int64, repeated, tag = "1")]
+    pub channel_ids: ::prost::alloc::vec::Vec<i64>,
+    #[prost(message, optional, tag = "2")]
+    pub filters: ::core::option::Option<Filters>,
+    #[prost(int64, tag = "3")]
+    pub start: i64,
+    #[prost(int64, tag = "4")]
+    pub end: i64,
+    #[prost(uint32, tag = "5")]
+    pub page: u32,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChannelProgrammes {
+    #[prost(message, optional, tag = "1")]
+    pub channel: ::core::option::Option<Channel>,
+    #[prost(message, repeated, tag = "2")]
+    pub programmes: ::prost::alloc::vec::Vec<Programme>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EpgGrid {
+    #[prost(message, repeated, tag = "1")]
+    pub channels: ::prost::alloc::vec::Vec<ChannelProgrammes>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ProgrammeList {
+    #[prost(message, repeated, tag = "1")]
+    pub programmes: ::prost::alloc::vec::Vec<Programme>,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct EpgChannel {
+    #[prost(int64, optional, tag = "1")]
+    pub id: ::core::option::Option<i64>,
+    #[prost(int64, tag = "2")]
+    pub source_id: i64,
+    #[prost(string, tag = "3")]
+    pub xmltv_id: ::prost::alloc::string::String,
+    #[prost(string, optional, tag = "4")]
+    pub display_name: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "5")]
+    pub icon: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EpgChannelList {
+    #[prost(message, repeated, tag = "1")]
+    pub epg_channels: ::prost::alloc::vec::Vec<EpgChannel>,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct EpgChannelQuery {
+    #[prost(int64, tag = "1")]
+    pub source_id: i64,
+    #[prost(string, optional, tag = "2")]
+    pub query: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(uint32, tag = "3")]
+    pub page: u32,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct EpgMatch {
+    #[prost(int64, tag = "1")]
+    pub channel_id: i64,
+    #[prost(message, optional, tag = "2")]
+    pub epg_channel: ::core::option::Option<EpgChannel>,
+    #[prost(uint32, optional, tag = "3")]
+    pub match_type: ::core::option::Option<u32>,
+    #[prost(uint32, tag = "4")]
+    pub confidence: u32,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct EpgOverride {
+    #[prost(int64, tag = "1")]
+    pub channel_id: i64,
+    #[prost(string, optional, tag = "2")]
+    pub xmltv_id: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct Reminder {
+    #[prost(int64, optional, tag = "1")]
+    pub id: ::core::option::Option<i64>,
+    #[prost(int64, optional, tag = "2")]
+    pub channel_id: ::core::option::Option<i64>,
+    #[prost(int64, tag = "3")]
+    pub source_id: i64,
+    #[prost(string, tag = "4")]
+    pub channel_name: ::prost::alloc::string::String,
+    #[prost(string, tag = "5")]
+    pub title: ::prost::alloc::string::String,
+    #[prost(int64, tag = "6")]
+    pub start: i64,
+    #[prost(int64, tag = "7")]
+    pub end: i64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReminderList {
+    #[prost(message, repeated, tag = "1")]
+    pub reminders: ::prost::alloc::vec::Vec<Reminder>,
+}
+#[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct AddReminder {
+    #[prost(int64, tag = "1")]
+    pub channel_id: i64,
+    #[prost(int64, tag = "2")]
+    pub start: i64,
+}
+#[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct GetUpcomingReminders {
+    #[prost(uint32, tag = "1")]
+    pub minutes: u32,
+}
+#[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct CatchupQuery {
+    #[prost(int64, tag = "1")]
+    pub channel_id: i64,
+    #[prost(int64, tag = "2")]
+    pub start: i64,
+    #[prost(uint32, tag = "3")]
+    pub duration_minutes: u32,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct CatchupUrl {
+    #[prost(string, tag = "1")]
+    pub url: ::prost::alloc::string::String,
+    #[prost(string, optional, tag = "2")]
+    pub fallback_url: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ResolvedStream {
+    #[prost(string, tag = "1")]
+    pub url: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub headers: ::core::option::Option<ChannelHttpHeaders>,
+    #[prost(int64, optional, tag = "3")]
+    pub position: ::core::option::Option<i64>,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct ImportProblem {
+    #[prost(int64, optional, tag = "1")]
+    pub line: ::core::option::Option<i64>,
+    #[prost(string, tag = "2")]
+    pub reason: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ImportReport {
+    #[prost(int64, tag = "1")]
+    pub source_id: i64,
+    #[prost(int64, tag = "2")]
+    pub created_at: i64,
+    #[prost(int64, tag = "3")]
+    pub imported: i64,
+    #[prost(int64, tag = "4")]
+    pub skipped: i64,
+    #[prost(int64, tag = "5")]
+    pub warnings: i64,
+    #[prost(map = "string, int64", tag = "6")]
+    pub reasons: ::std::collections::HashMap<::prost::alloc::string::String, i64>,
+    #[prost(message, repeated, tag = "7")]
+    pub problems: ::prost::alloc::vec::Vec<ImportProblem>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChannelAttributes {
+    #[prost(map = "string, string", tag = "1")]
+    pub attributes: ::std::collections::HashMap<
+        ::prost::alloc::string::String,
+        ::prost::alloc::string::String,
+    >,
+}
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct FfiResult {
     #[prost(bool, tag = "1")]
     pub success: bool,
     #[prost(string, optional, tag = "2")]
     pub error_message: ::core::option::Option<::prost::alloc::string::String>,
-    #[prost(oneof = "ffi_result::Data", tags = "3, 4, 6, 7, 8, 9, 10, 11")]
+    #[prost(
+        oneof = "ffi_result::Data",
+        tags = "3, 4, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21"
+    )]
     pub data: ::core::option::Option<ffi_result::Data>,
 }
 /// Nested message and enum types in `FFIResult`.
@@ -219,6 +447,26 @@ pub mod ffi_result {
         EnabledSourcesMinimal(super::GetEnabledSourcesMinimal),
         #[prost(message, tag = "11")]
         SourceList(super::SourceList),
+        #[prost(message, tag = "12")]
+        EpgGrid(super::EpgGrid),
+        #[prost(message, tag = "13")]
+        ProgrammeList(super::ProgrammeList),
+        #[prost(message, tag = "14")]
+        EpgMatch(super::EpgMatch),
+        #[prost(message, tag = "15")]
+        EpgChannelList(super::EpgChannelList),
+        #[prost(message, tag = "16")]
+        ReminderList(super::ReminderList),
+        #[prost(message, tag = "17")]
+        Id(super::IdMessage),
+        #[prost(message, tag = "18")]
+        CatchupUrl(super::CatchupUrl),
+        #[prost(message, tag = "19")]
+        ChannelAttributes(super::ChannelAttributes),
+        #[prost(message, tag = "20")]
+        ResolvedStream(super::ResolvedStream),
+        #[prost(message, tag = "21")]
+        ImportReport(super::ImportReport),
     }
 }
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
@@ -0,0 +1,63 @@
+use anyhow::Error;
+
+use crate::types::{ImportProblem, ImportReport};
+
+pub const MAX_PROBLEMS: usize = 50;
+
+impl ImportReport {
+    pub fn new(source_id: i64) -> Self {
+        ImportReport {
+            source_id,
+            created_at: chrono::Utc::now().timestamp(),
+            ..Default::default()
+        }
+    }
+
+    pub fn add_skipped(&mut self, line: Option<i64>, error: Error) {
+        self.skipped += 1;
+        self.add_problem(line, error);
+    }
+
+    pub fn add_warning(&mut self, line: Option<i64>, error: Error) {
+        self.warnings += 1;
+        self.add_problem(line, error);
+    }
+
+    fn add_problem(&mut self, line: Option<i64>, error: Error) {
+        let reason = error.root_cause().to_string();
+        tracing::error!("{:?}", error);
+        *self.reasons.entry(reason.clone()).or_default() += 1;
+        if self.problems.len() < MAX_PROBLEMS {
+            self.problems.push(ImportProblem { line, reason });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_import_report {
+    use anyhow::anyhow;
+
+    use crate::import_report::MAX_PROBLEMS;
+    use crate::types::ImportReport;
+
+    #[test]
+    fn test_add_problems() {
+        let mut report = ImportReport::new(1);
+        for line in 0..MAX_PROBLEMS as i64 + 10 {
+            report.add_skipped(
+                Some(line),
+                anyhow!("missing URL").context("Failed to process"),
+            );
+        }
+        report.add_warning(None, anyhow!("missing group"));
+        assert_eq!(report.skipped, MAX_PROBLEMS as i64 + 10);
+        assert_eq!(report.warnings, 1);
+        assert_eq!(report.problems.len(), MAX_PROBLEMS);
+        assert_eq!(report.problems[0].reason, "missing URL");
+        assert_eq!(
+            report.reasons.get("missing URL"),
+            Some(&(MAX_PROBLEMS as i64 + 10))
+        );
+        assert_eq!(report.reasons.get("missing group"), Some(&1));
+    }
+}
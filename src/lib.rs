@@ -5,7 +5,12 @@ use crate::generated_proto::ToggleFavorite;
 use anyhow::Ok;
 
 mod c;
+mod catchup;
+mod catchup_type;
+mod epg;
+mod epg_match_type;
 mod generated_proto;
+mod import_report;
 mod log;
 mod m3u;
 mod media_type;
@@ -29,6 +34,7 @@ impl From<crate::types::Settings> for crate::generated_proto::Settings {
             show_livestreams: settings.show_livestreams,
             show_series: settings.show_series,
             show_movies: settings.show_movies,
+            epg_retention_days: settings.epg_retention_days.map(|d| d as u32),
         }
     }
 }
@@ -44,6 +50,7 @@ impl From<crate::generated_proto::Settings> for crate::types::Settings {
             show_livestreams: settings.show_livestreams,
             show_movies: settings.show_movies,
             show_series: settings.show_series,
+            epg_retention_days: settings.epg_retention_days.map(|d| d as u16),
         }
     }
 }
@@ -62,6 +69,10 @@ impl From<crate::generated_proto::Source> for crate::types::Source {
             stream_user_agent: source.stream_user_agent,
             user_agent: source.user_agent,
             enabled: source.enabled,
+            epg_shift: source.epg_shift,
+            ignore_ssl: source.ignore_ssl,
+            encoding: source.encoding,
+            expand_playlists: source.expand_playlists,
         }
     }
 }
@@ -80,6 +91,10 @@ impl From<crate::types::Source> for crate::generated_proto::Source {
             user_agent: source.user_agent,
             stream_user_agent: source.stream_user_agent,
             last_updated: source.last_updated,
+            epg_shift: source.epg_shift,
+            ignore_ssl: source.ignore_ssl,
+            encoding: source.encoding,
+            expand_playlists: source.expand_playlists,
         }
     }
 }
@@ -106,6 +121,7 @@ impl From<crate::generated_proto::Filters> for crate::types::Filters {
             use_keywords: filters.use_keywords,
             sort: filters.sort as u8,
             season: filters.season,
+            search_programmes: filters.search_programmes,
         }
     }
 }
@@ -125,8 +141,30 @@ impl From<crate::types::Channel> for crate::generated_proto::Channel {
             favorite: channel.favorite,
             stream_id: channel.stream_id.map(|s| s as u64),
             tv_archive: channel.tv_archive,
+            tv_archive_duration: channel.tv_archive_duration,
+            channel_number: channel.channel_number,
             season_id: channel.season_id,
             episode_num: channel.episode_num,
+            duration: channel.duration,
+            language: channel.language,
+            country: channel.country,
+            epg_now: channel.epg_now.map(Into::into),
+            epg_next: channel.epg_next.map(Into::into),
+        }
+    }
+}
+
+impl From<crate::types::Programme> for crate::generated_proto::Programme {
+    fn from(programme: crate::types::Programme) -> Self {
+        crate::generated_proto::Programme {
+            id: programme.id,
+            epg_channel_id: programme.epg_channel_id,
+            title: programme.title,
+            description: programme.description,
+            category: programme.category,
+            icon: programme.icon,
+            start: programme.start,
+            end: programme.end,
         }
     }
 }
@@ -138,6 +176,90 @@ impl From<Vec<crate::types::Channel>> for crate::generated_proto::ChannelList {
     }
 }
 
+impl From<Vec<crate::types::Programme>> for crate::generated_proto::ProgrammeList {
+    fn from(programmes: Vec<crate::types::Programme>) -> Self {
+        crate::generated_proto::ProgrammeList {
+            programmes: programmes.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<crate::types::EpgChannel> for crate::generated_proto::EpgChannel {
+    fn from(channel: crate::types::EpgChannel) -> Self {
+        crate::generated_proto::EpgChannel {
+            id: channel.id,
+            source_id: channel.source_id,
+            xmltv_id: channel.xmltv_id,
+            display_name: channel.display_name,
+            icon: channel.icon,
+        }
+    }
+}
+
+impl From<Vec<crate::types::EpgChannel>> for crate::generated_proto::EpgChannelList {
+    fn from(channels: Vec<crate::types::EpgChannel>) -> Self {
+        crate::generated_proto::EpgChannelList {
+            epg_channels: channels.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<crate::types::EpgMatch> for crate::generated_proto::EpgMatch {
+    fn from(epg_match: crate::types::EpgMatch) -> Self {
+        crate::generated_proto::EpgMatch {
+            channel_id: epg_match.channel_id,
+            epg_channel: epg_match.epg_channel.map(Into::into),
+            match_type: epg_match.match_type.map(|m| m as u32),
+            confidence: epg_match.confidence as u32,
+        }
+    }
+}
+
+impl From<Vec<crate::types::Reminder>> for crate::generated_proto::ReminderList {
+    fn from(reminders: Vec<crate::types::Reminder>) -> Self {
+        crate::generated_proto::ReminderList {
+            reminders: reminders
+                .into_iter()
+                .map(|r| crate::generated_proto::Reminder {
+                    id: r.id,
+                    channel_id: r.channel_id,
+                    source_id: r.source_id,
+                    channel_name: r.channel_name,
+                    title: r.title,
+                    start: r.start,
+                    end: r.end,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<crate::generated_proto::EpgGridQuery> for crate::types::EpgGridQuery {
+    fn from(query: crate::generated_proto::EpgGridQuery) -> Self {
+        crate::types::EpgGridQuery {
+            channel_ids: query.channel_ids,
+            filters: query.filters.map(Into::into),
+            start: query.start,
+            end: query.end,
+            page: query.page as u8,
+        }
+    }
+}
+
+impl From<Vec<crate::types::ChannelProgrammes>> for crate::generated_proto::EpgGrid {
+    fn from(channels: Vec<crate::types::ChannelProgrammes>) -> Self {
+        crate::generated_proto::EpgGrid {
+            channels: channels
+                .into_iter()
+                .map(|c| crate::generated_proto::ChannelProgrammes {
+                    channel: Some(c.channel.into()),
+                    programmes: c.programmes.into_iter().map(Into::into).collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
 impl From<crate::types::ChannelHttpHeaders> for crate::generated_proto::ChannelHttpHeaders {
     fn from(headers: crate::types::ChannelHttpHeaders) -> Self {
         crate::generated_proto::ChannelHttpHeaders {
@@ -147,6 +269,39 @@ impl From<crate::types::ChannelHttpHeaders> for crate::generated_proto::ChannelH
             user_agent: headers.user_agent,
             http_origin: headers.http_origin,
             ignore_ssl: headers.ignore_ssl,
+            cookie: headers.cookie,
+            extra_headers: headers.extra_headers,
+        }
+    }
+}
+
+impl From<crate::types::ResolvedStream> for crate::generated_proto::ResolvedStream {
+    fn from(stream: crate::types::ResolvedStream) -> Self {
+        crate::generated_proto::ResolvedStream {
+            url: stream.url,
+            headers: Some(crate::generated_proto::ChannelHttpHeaders::from(stream.headers)),
+            position: stream.position,
+        }
+    }
+}
+
+impl From<crate::types::ImportReport> for crate::generated_proto::ImportReport {
+    fn from(report: crate::types::ImportReport) -> Self {
+        crate::generated_proto::ImportReport {
+            source_id: report.source_id,
+            created_at: report.created_at,
+            imported: report.imported,
+            skipped: report.skipped,
+            warnings: report.warnings,
+            reasons: report.reasons,
+            problems: report
+                .problems
+                .into_iter()
+                .map(|problem| crate::generated_proto::ImportProblem {
+                    line: problem.line,
+                    reason: problem.reason,
+                })
+                .collect(),
         }
     }
 }
@@ -179,7 +334,10 @@ pub extern "C" fn process_source(task_id: u64, callback: FfiCallback, ptr: *cons
         ptr,
         len,
         |source: crate::generated_proto::Source| async move {
-            utils::process_source(crate::types::Source::from(source)).await
+            let report = utils::process_source(crate::types::Source::from(source)).await?;
+            Ok(generated_proto::ffi_result::Data::ImportReport(
+                crate::generated_proto::ImportReport::from(report),
+            ))
         },
     );
 }
@@ -192,7 +350,10 @@ pub extern "C" fn refresh_source(task_id: u64, callback: FfiCallback, ptr: *cons
         ptr,
         len,
         |source: crate::generated_proto::Source| async move {
-            utils::refresh_source(crate::types::Source::from(source)).await
+            let report = utils::refresh_source(crate::types::Source::from(source)).await?;
+            Ok(generated_proto::ffi_result::Data::ImportReport(
+                crate::generated_proto::ImportReport::from(report),
+            ))
         },
     );
 }
@@ -221,6 +382,23 @@ pub extern "C" fn get_channels(task_id: u64, callback: FfiCallback, ptr: *const
     )
 }
 
+#[unsafe(no_mangle)]
+pub extern "C" fn get_epg_grid(task_id: u64, callback: FfiCallback, ptr: *const u8, len: usize) {
+    c::queue_blocking_with_message(
+        task_id,
+        callback,
+        ptr,
+        len,
+        |query: crate::generated_proto::EpgGridQuery| {
+            Ok(generated_proto::ffi_result::Data::EpgGrid(
+                crate::generated_proto::EpgGrid::from(sql::get_epg_grid(
+                    crate::types::EpgGridQuery::from(query),
+                )?),
+            ))
+        },
+    )
+}
+
 #[unsafe(no_mangle)]
 pub extern "C" fn favorite(task_id: u64, callback: FfiCallback, ptr: *const u8, len: usize) {
     c::queue_blocking_with_message(
@@ -454,6 +632,231 @@ pub extern "C" fn set_source_enabled(task_id: u64, callback: FfiCallback, ptr: *
     )
 }
 
+#[unsafe(no_mangle)]
+pub extern "C" fn import_epg(task_id: u64, callback: FfiCallback, ptr: *const u8, len: usize) {
+    c::queue_async_with_message(
+        task_id,
+        callback,
+        ptr,
+        len,
+        |import_epg_msg: crate::generated_proto::ImportEpg| async move {
+            epg::import_epg(import_epg_msg.source_id, import_epg_msg.url).await
+        },
+    );
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn refresh_epg(task_id: u64, callback: FfiCallback, ptr: *const u8, len: usize) {
+    c::queue_async_with_message(
+        task_id,
+        callback,
+        ptr,
+        len,
+        |id: crate::generated_proto::IdMessage| async move { epg::refresh_epg(id.value).await },
+    );
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn get_short_epg(task_id: u64, callback: FfiCallback, ptr: *const u8, len: usize) {
+    c::queue_async_with_message(
+        task_id,
+        callback,
+        ptr,
+        len,
+        |id: crate::generated_proto::IdMessage| async move {
+            Ok(generated_proto::ffi_result::Data::ProgrammeList(
+                crate::generated_proto::ProgrammeList::from(xtream::get_short_epg(id.value).await?),
+            ))
+        },
+    );
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn get_epg_match(task_id: u64, callback: FfiCallback, ptr: *const u8, len: usize) {
+    c::queue_blocking_with_message(
+        task_id,
+        callback,
+        ptr,
+        len,
+        |id: crate::generated_proto::IdMessage| {
+            Ok(generated_proto::ffi_result::Data::EpgMatch(
+                crate::generated_proto::EpgMatch::from(epg::get_epg_match(id.value)?),
+            ))
+        },
+    )
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn set_epg_override(task_id: u64, callback: FfiCallback, ptr: *const u8, len: usize) {
+    c::queue_blocking_with_message(
+        task_id,
+        callback,
+        ptr,
+        len,
+        |epg_override: crate::generated_proto::EpgOverride| {
+            epg::set_epg_override(epg_override.channel_id, epg_override.xmltv_id)
+        },
+    )
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn delete_epg_override(task_id: u64, callback: FfiCallback, ptr: *const u8, len: usize) {
+    c::queue_blocking_with_message(
+        task_id,
+        callback,
+        ptr,
+        len,
+        |id: crate::generated_proto::IdMessage| epg::delete_epg_override(id.value),
+    )
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn search_epg_channels(task_id: u64, callback: FfiCallback, ptr: *const u8, len: usize) {
+    c::queue_blocking_with_message(
+        task_id,
+        callback,
+        ptr,
+        len,
+        |query: crate::generated_proto::EpgChannelQuery| {
+            Ok(generated_proto::ffi_result::Data::EpgChannelList(
+                crate::generated_proto::EpgChannelList::from(sql::search_epg_channels(
+                    query.source_id,
+                    query.query,
+                    query.page as u8,
+                )?),
+            ))
+        },
+    )
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn add_reminder(task_id: u64, callback: FfiCallback, ptr: *const u8, len: usize) {
+    c::queue_blocking_with_message(
+        task_id,
+        callback,
+        ptr,
+        len,
+        |add_reminder_msg: crate::generated_proto::AddReminder| {
+            Ok(generated_proto::ffi_result::Data::Id(
+                crate::generated_proto::IdMessage {
+                    value: epg::add_reminder(add_reminder_msg.channel_id, add_reminder_msg.start)?,
+                },
+            ))
+        },
+    )
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn delete_reminder(task_id: u64, callback: FfiCallback, ptr: *const u8, len: usize) {
+    c::queue_blocking_with_message(
+        task_id,
+        callback,
+        ptr,
+        len,
+        |id: crate::generated_proto::IdMessage| sql::delete_reminder(id.value),
+    )
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn get_upcoming_reminders(task_id: u64, callback: FfiCallback, ptr: *const u8, len: usize) {
+    c::queue_blocking_with_message(
+        task_id,
+        callback,
+        ptr,
+        len,
+        |msg: crate::generated_proto::GetUpcomingReminders| {
+            Ok(generated_proto::ffi_result::Data::ReminderList(
+                crate::generated_proto::ReminderList::from(epg::get_upcoming_reminders(
+                    msg.minutes,
+                )?),
+            ))
+        },
+    )
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn get_catchup_url(task_id: u64, callback: FfiCallback, ptr: *const u8, len: usize) {
+    c::queue_blocking_with_message(
+        task_id,
+        callback,
+        ptr,
+        len,
+        |query: crate::generated_proto::CatchupQuery| {
+            let url = catchup::get_catchup_url(query.channel_id, query.start, query.duration_minutes)?;
+            Ok(generated_proto::ffi_result::Data::CatchupUrl(
+                crate::generated_proto::CatchupUrl {
+                    url: url.url,
+                    fallback_url: url.fallback_url,
+                },
+            ))
+        },
+    )
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn get_channel_attributes(task_id: u64, callback: FfiCallback, ptr: *const u8, len: usize) {
+    c::queue_blocking_with_message(
+        task_id,
+        callback,
+        ptr,
+        len,
+        |id: crate::generated_proto::IdMessage| {
+            Ok(generated_proto::ffi_result::Data::ChannelAttributes(
+                crate::generated_proto::ChannelAttributes {
+                    attributes: sql::get_channel_attributes(id.value)?,
+                },
+            ))
+        },
+    )
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn tune_to_number(task_id: u64, callback: FfiCallback, ptr: *const u8, len: usize) {
+    c::queue_blocking_with_message(
+        task_id,
+        callback,
+        ptr,
+        len,
+        |number: crate::generated_proto::IdMessage| {
+            Ok(generated_proto::ffi_result::Data::ChannelList(
+                crate::generated_proto::ChannelList::from(sql::get_channels_by_number(number.value)?),
+            ))
+        },
+    )
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn resolve_stream(task_id: u64, callback: FfiCallback, ptr: *const u8, len: usize) {
+    c::queue_blocking_with_message(
+        task_id,
+        callback,
+        ptr,
+        len,
+        |id: crate::generated_proto::IdMessage| {
+            Ok(generated_proto::ffi_result::Data::ResolvedStream(
+                crate::generated_proto::ResolvedStream::from(utils::resolve_stream(id.value)?),
+            ))
+        },
+    )
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn get_import_report(task_id: u64, callback: FfiCallback, ptr: *const u8, len: usize) {
+    c::queue_blocking_with_message(
+        task_id,
+        callback,
+        ptr,
+        len,
+        |id: crate::generated_proto::IdMessage| {
+            Ok(sql::get_import_report(id.value)?.map(|report| {
+                generated_proto::ffi_result::Data::ImportReport(
+                    crate::generated_proto::ImportReport::from(report),
+                )
+            }))
+        },
+    )
+}
+
 #[unsafe(no_mangle)]
 pub extern "C" fn free_message(ptr: *mut u8, len: usize) {
     unsafe {
@@ -1,37 +1,42 @@
-use std::io::Write;
-use std::path::PathBuf;
-use std::str::FromStr;
 use std::sync::LazyLock;
+use std::time::Duration;
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     fs::File,
-    io::{BufRead, BufReader},
+    io::{BufRead, BufReader, Cursor, ErrorKind, Read},
+    path::{Path, PathBuf},
 };
 
-use anyhow::{Context, Result, bail};
+use anyhow::{Context, Result, anyhow, bail};
+use encoding_rs::{Encoding, UTF_8, WINDOWS_1252};
+use encoding_rs_io::DecodeReaderBytesBuilder;
+use flate2::read::MultiGzDecoder;
+use percent_encoding::percent_decode_str;
 use regex::{Captures, Regex};
-use rusqlite::Transaction;
+use reqwest::{Client, Response, Url, header};
+use rusqlite::{Transaction, TransactionBehavior};
+use tokio::runtime::Handle;
+use tokio::sync::mpsc::Receiver;
 use types::{Channel, Source};
+use zip::ZipArchive;
 
-use crate::types::ChannelPreserve;
-use crate::utils;
+use crate::types::{ChannelPreserve, ImportReport, Season};
 use crate::{
-    media_type, source_type,
+    catchup, epg, media_type,
     sql::{self, set_channel_group_id},
     types::{self, ChannelHttpHeaders},
-    utils::get_user_agent_from_source,
+    utils::get_http_client,
 };
 
-static NAME_REGEX: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r#"tvg-name="(?P<name>[^"]*)""#).unwrap());
-static NAME_REGEX_ALT: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r#",(?P<name>[^\n\r\t]*)"#).unwrap());
-static ID_REGEX: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r#"tvg-id="(?P<id>[^"]*)""#).unwrap());
-static LOGO_REGEX: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r#"tvg-logo="(?P<logo>[^"]*)""#).unwrap());
-static GROUP_REGEX: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r#"group-title="(?P<group>[^"]*)""#).unwrap());
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const MAX_PLAYLIST_DEPTH: usize = 3;
+const MAX_SERIES_ID: u64 = (1 << 53) - 1;
+const MAX_NESTED_PLAYLIST_SIZE: u64 = 16 * 1024 * 1024;
+const NESTED_PLAYLIST_TIMEOUT: Duration = Duration::from_secs(10);
+const VOD_EXTENSIONS: &[&str] = &["mp4", "mkv", "avi", "mov", "m4v", "wmv", "webm"];
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
 
 static HTTP_ORIGIN_REGEX: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r#"http-origin=(?P<origin>.+)"#).unwrap());
@@ -39,28 +44,419 @@ static HTTP_REFERRER_REGEX: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r#"http-referrer=(?P<referrer>.+)"#).unwrap());
 static HTTP_USER_AGENT_REGEX: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r#"http-user-agent=(?P<user_agent>.+)"#).unwrap());
+static EPISODE_REGEXES: LazyLock<[Regex; 3]> = LazyLock::new(|| {
+    [
+        r#"(?i)\bS(?P<season>\d{1,3})[\s._-]*E(?P<episode>\d{1,4})\b"#,
+        r#"(?i)\bSeason[\s._-]*(?P<season>\d{1,3})[\s,._-]*Episode[\s._-]*(?P<episode>\d{1,4})\b"#,
+        r#"(?i)\b(?P<season>\d{1,2})x(?P<episode>\d{2,3})\b"#,
+    ]
+    .map(|regex| Regex::new(regex).unwrap())
+});
+
+#[derive(Default, Debug)]
+struct M3UAttributes {
+    attributes: HashMap<String, String>,
+    title: Option<String>,
+    duration: Option<i64>,
+}
+
+impl M3UAttributes {
+    fn take(&mut self, key: &str) -> Option<String> {
+        self.attributes
+            .remove(key)
+            .filter(|value| !value.is_empty())
+    }
+
+    fn take_any(&mut self, keys: &[&str]) -> Option<String> {
+        keys.iter()
+            .map(|key| self.take(key))
+            .fold(None, |found, value| found.or(value))
+    }
+}
 
 struct M3UProcessing {
     channel_line: Option<String>,
+    channel_line_count: usize,
     channel_headers: Option<ChannelHttpHeaders>,
     channel_headers_set: bool,
     last_non_empty_line: Option<String>,
     groups: HashMap<String, i64>,
     source_id: i64,
     line_count: usize,
+    epg_urls: Vec<String>,
+    tvg_shift: Option<f64>,
+    position: i64,
+    report: ImportReport,
+    base: PlaylistBase,
+    expanded: HashSet<String>,
+    nested: HashMap<String, Result<NestedPlaylist>>,
+    series: HashSet<i64>,
+}
+
+#[derive(Debug, PartialEq)]
+struct EpisodeInfo {
+    series_name: String,
+    season_number: i64,
+    episode_num: i64,
+}
+
+enum PlaylistBase {
+    Url(Url),
+    Directory(PathBuf),
+}
+
+struct NestedPlaylist {
+    base: PlaylistBase,
+    lines: Vec<String>,
+}
+
+struct ChunkReader {
+    receiver: Receiver<std::io::Result<Vec<u8>>>,
+    chunk: Cursor<Vec<u8>>,
+}
+
+struct ResponseReader {
+    runtime: Handle,
+    response: Response,
+    chunk: Cursor<Vec<u8>>,
+}
+
+struct SizeLimitedReader<R> {
+    reader: R,
+    remaining: u64,
+}
+
+struct PlaylistLines {
+    reader: Box<dyn BufRead + Send>,
+    encoding: &'static Encoding,
+}
+
+impl PlaylistLines {
+    fn new(mut reader: Box<dyn BufRead + Send>) -> Result<Self> {
+        let Some((encoding, bom_length)) = Encoding::for_bom(reader.fill_buf()?) else {
+            return Ok(PlaylistLines {
+                reader,
+                encoding: UTF_8,
+            });
+        };
+        if encoding == UTF_8 {
+            reader.consume(bom_length);
+            return Ok(PlaylistLines { reader, encoding });
+        }
+        let reader = DecodeReaderBytesBuilder::new()
+            .encoding(Some(encoding))
+            .build(reader);
+        Ok(PlaylistLines {
+            reader: Box::new(BufReader::new(reader)),
+            encoding,
+        })
+    }
+}
+
+impl Iterator for PlaylistLines {
+    type Item = std::io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut line = Vec::new();
+        match self.reader.read_until(b'\n', &mut line) {
+            Ok(0) => return None,
+            Ok(_) => {}
+            Err(e) => return Some(Err(e)),
+        }
+        while line.ends_with(b"\n") || line.ends_with(b"\r") {
+            line.pop();
+        }
+        Some(Ok(String::from_utf8(line).unwrap_or_else(|e| {
+            self.encoding = WINDOWS_1252;
+            WINDOWS_1252
+                .decode_without_bom_handling(e.as_bytes())
+                .0
+                .into_owned()
+        })))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum PlaylistFormat {
+    Plain,
+    Gzip,
+    Zip,
+}
+
+impl Read for ChunkReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            let read = self.chunk.read(buf)?;
+            if read > 0 || buf.is_empty() {
+                return Ok(read);
+            }
+            match self.receiver.blocking_recv() {
+                Some(chunk) => self.chunk = Cursor::new(chunk?),
+                None => return Ok(0),
+            }
+        }
+    }
+}
+
+impl Read for ResponseReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            let read = self.chunk.read(buf)?;
+            if read > 0 || buf.is_empty() {
+                return Ok(read);
+            }
+            match self
+                .runtime
+                .block_on(self.response.chunk())
+                .map_err(std::io::Error::other)?
+            {
+                Some(chunk) => self.chunk = Cursor::new(chunk.into()),
+                None => return Ok(0),
+            }
+        }
+    }
+}
+
+impl<R: Read> Read for SizeLimitedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.reader.read(buf)?;
+        self.remaining = self.remaining.checked_sub(read as u64).ok_or_else(|| {
+            std::io::Error::other(format!(
+                "Playlist is larger than {MAX_NESTED_PLAYLIST_SIZE} bytes"
+            ))
+        })?;
+        Ok(read)
+    }
+}
+
+impl<R> SizeLimitedReader<R> {
+    fn new(reader: R) -> Self {
+        SizeLimitedReader {
+            reader,
+            remaining: MAX_NESTED_PLAYLIST_SIZE,
+        }
+    }
+}
+
+pub fn read_m3u8(source: Source, wipe: bool) -> Result<ImportReport> {
+    let path = source.url.clone().context("no file path found")?;
+    let file = File::open(&path).context("Failed to open m3u8 file")?;
+    let base = PlaylistBase::Directory(
+        Path::new(&path)
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default(),
+    );
+    read_m3u8_from_reader(open_playlist(file, &path)?, source, wipe, base)
+}
+
+fn resolve_url(url: String, base: &PlaylistBase) -> String {
+    let url = url.trim().to_string();
+    if url.is_empty() || Url::parse(&url).is_ok() {
+        return url;
+    }
+    match base {
+        PlaylistBase::Url(base) => base.join(&url).map(String::from).unwrap_or(url),
+        PlaylistBase::Directory(directory) => directory.join(&url).to_string_lossy().to_string(),
+    }
+}
+
+fn get_url_path(url: &str) -> String {
+    Url::parse(url)
+        .map(|url| url.path().to_string())
+        .unwrap_or_else(|_| url.to_string())
+}
+
+fn is_playlist_url(url: &str) -> bool {
+    let path = get_url_path(url).to_lowercase();
+    path.ends_with(".m3u") || path.ends_with(".m3u8")
+}
+
+fn is_nested_playlist(lines: &[String]) -> bool {
+    !lines.iter().any(|line| line.starts_with("#EXT-X-"))
+        && lines
+            .iter()
+            .any(|line| line.to_uppercase().starts_with("#EXTINF"))
+}
+
+fn prefetch_nested_playlists<'a>(
+    lines: impl Iterator<Item = &'a String>,
+    base: &PlaylistBase,
+    depth: usize,
+    client: &Client,
+    nested: &mut HashMap<String, Result<NestedPlaylist>>,
+    checked: &mut HashSet<String>,
+) {
+    if depth >= MAX_PLAYLIST_DEPTH {
+        return;
+    }
+    for line in lines.filter(|line| !line.trim().is_empty() && !line.starts_with('#')) {
+        let url = resolve_url(get_pipe_headers(line).0, base);
+        if !is_playlist_url(&url) || !checked.insert(url.clone()) {
+            continue;
+        }
+        match read_nested_playlist(&url, client) {
+            Ok(Some(playlist)) => {
+                prefetch_nested_playlists(
+                    playlist.lines.iter(),
+                    &playlist.base,
+                    depth + 1,
+                    client,
+                    nested,
+                    checked,
+                );
+                nested.insert(url, Ok(playlist));
+            }
+            Ok(None) => {}
+            Err(e) => {
+                nested.insert(url, Err(e));
+            }
+        }
+    }
 }
 
-pub fn read_m3u8(mut source: Source, wipe: bool) -> Result<()> {
-    let path = match source.source_type {
-        source_type::M3U_LINK => get_tmp_path()?,
-        _ => source.url.clone().context("no file path found")?,
+fn read_nested_playlist(url: &str, client: &Client) -> Result<Option<NestedPlaylist>> {
+    let (base, reader) = match Url::parse(url) {
+        Ok(url) if url.scheme() == "http" || url.scheme() == "https" => {
+            let runtime = Handle::try_current()?;
+            let response = runtime
+                .block_on(client.get(url).timeout(NESTED_PLAYLIST_TIMEOUT).send())?
+                .error_for_status()?;
+            let base = PlaylistBase::Url(response.url().clone());
+            let hint = get_response_hint(&response);
+            let reader = ResponseReader {
+                runtime,
+                response,
+                chunk: Cursor::new(Vec::new()),
+            };
+            (base, open_playlist(SizeLimitedReader::new(reader), &hint)?)
+        }
+        Ok(_) => return Ok(None),
+        Err(_) => {
+            let base = PlaylistBase::Directory(
+                Path::new(url)
+                    .parent()
+                    .map(Path::to_path_buf)
+                    .unwrap_or_default(),
+            );
+            (
+                base,
+                open_playlist(SizeLimitedReader::new(File::open(url)?), url)?,
+            )
+        }
     };
-    let file = File::open(path).context("Failed to open m3u8 file")?;
-    let reader = BufReader::new(file);
-    let mut lines = reader.lines().enumerate();
-    let mut sql = sql::get_conn()?;
+    let mut lines = Vec::new();
+    let mut size = 0;
+    for line in PlaylistLines::new(reader)? {
+        let line = line?;
+        if line.starts_with("#EXT-X-") {
+            return Ok(None);
+        }
+        size += line.len() as u64;
+        if size > MAX_NESTED_PLAYLIST_SIZE {
+            bail!("Playlist is larger than {MAX_NESTED_PLAYLIST_SIZE} bytes");
+        }
+        lines.push(line);
+    }
+    Ok(is_nested_playlist(&lines).then_some(NestedPlaylist { base, lines }))
+}
+
+fn get_playlist_format(magic: &[u8], hint: &str) -> PlaylistFormat {
+    let hint = hint.to_lowercase();
+    if magic.starts_with(GZIP_MAGIC) {
+        PlaylistFormat::Gzip
+    } else if magic.starts_with(ZIP_MAGIC) {
+        PlaylistFormat::Zip
+    } else if magic.len() >= ZIP_MAGIC.len() {
+        PlaylistFormat::Plain
+    } else if hint.contains("gzip") || hint.ends_with(".gz") {
+        PlaylistFormat::Gzip
+    } else if hint.contains("zip") {
+        PlaylistFormat::Zip
+    } else {
+        PlaylistFormat::Plain
+    }
+}
+
+fn open_playlist<R: Read + Send + 'static>(
+    mut reader: R,
+    hint: &str,
+) -> Result<Box<dyn BufRead + Send>> {
+    let mut magic = Vec::with_capacity(ZIP_MAGIC.len());
+    reader
+        .by_ref()
+        .take(ZIP_MAGIC.len() as u64)
+        .read_to_end(&mut magic)?;
+    let format = get_playlist_format(&magic, hint);
+    let mut reader = Cursor::new(magic).chain(reader);
+    Ok(match format {
+        PlaylistFormat::Plain => Box::new(BufReader::new(reader)),
+        PlaylistFormat::Gzip => Box::new(BufReader::new(MultiGzDecoder::new(reader))),
+        PlaylistFormat::Zip => {
+            let mut data = Vec::new();
+            reader.read_to_end(&mut data)?;
+            Box::new(Cursor::new(read_playlist_from_zip(data)?))
+        }
+    })
+}
+
+fn read_playlist_from_zip(data: Vec<u8>) -> Result<Vec<u8>> {
+    let mut archive = ZipArchive::new(Cursor::new(data)).context("Failed to open zip archive")?;
+    let names: Vec<String> = archive
+        .file_names()
+        .filter(|name| !name.ends_with('/'))
+        .map(String::from)
+        .collect();
+    let name = names
+        .iter()
+        .find(|name| {
+            let name = name.to_lowercase();
+            name.ends_with(".m3u") || name.ends_with(".m3u8")
+        })
+        .or(names.first())
+        .context("Zip archive contains no playlist")?;
+    let mut playlist = Vec::new();
+    archive.by_name(name)?.read_to_end(&mut playlist)?;
+    Ok(playlist)
+}
+
+fn get_response_hint(response: &reqwest::Response) -> String {
+    [header::CONTENT_ENCODING, header::CONTENT_TYPE]
+        .iter()
+        .filter_map(|name| response.headers().get(name)?.to_str().ok())
+        .chain(std::iter::once(response.url().path()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn read_m3u8_from_reader(
+    reader: Box<dyn BufRead + Send>,
+    mut source: Source,
+    wipe: bool,
+    base: PlaylistBase,
+) -> Result<ImportReport> {
+    let mut playlist = PlaylistLines::new(reader)?;
+    let lines = read_playlist_lines(&mut playlist)?;
+    let expanded: HashSet<String> = source
+        .url
+        .clone()
+        .filter(|_| source.expand_playlists)
+        .into_iter()
+        .collect();
+    let mut nested = HashMap::new();
+    if source.expand_playlists {
+        prefetch_nested_playlists(
+            lines.iter().filter_map(|(_, line)| line.as_ref().ok()),
+            &base,
+            0,
+            &get_http_client(&source)?,
+            &mut nested,
+            &mut expanded.clone(),
+        );
+    }
+    let mut sql = sql::get_import_conn()?;
     let mut channel_preserve: Vec<ChannelPreserve> = Vec::new();
-    let tx = sql.transaction()?;
+    let tx = sql.transaction_with_behavior(TransactionBehavior::Immediate)?;
     if wipe {
         channel_preserve =
             sql::get_preserve(&tx, source.id.context("no source id")?).unwrap_or_default();
@@ -68,28 +464,99 @@ pub fn read_m3u8(mut source: Source, wipe: bool) -> Result<()> {
     } else {
         source.id = Some(sql::create_or_find_source_by_name(&tx, &source)?);
     }
+    let source_id = source.id.context("no source id")?;
     let mut processing = M3UProcessing {
         channel_headers: None,
         channel_headers_set: false,
         channel_line: None,
+        channel_line_count: 0,
         groups: HashMap::new(),
         last_non_empty_line: None,
-        source_id: source.id.context("no source id")?,
+        source_id,
         line_count: 0,
+        epg_urls: Vec::new(),
+        tvg_shift: None,
+        position: 0,
+        report: ImportReport::new(source_id),
+        base,
+        expanded,
+        nested,
+        series: HashSet::new(),
     };
+    process_lines(lines.into_iter(), &mut processing, &tx)?;
+    try_commit_channel(&mut processing, &tx);
+    sql::set_source_epg_urls(&tx, processing.source_id, &processing.epg_urls)?;
+    sql::set_source_tvg_shift(&tx, processing.source_id, processing.tvg_shift)?;
+    sql::set_source_encoding(&tx, processing.source_id, playlist.encoding.name())?;
+    epg::match_channels(&tx, processing.source_id)?;
+    if wipe {
+        sql::restore_preserve(&tx, source.id.context("no source id")?, channel_preserve)?;
+    }
+    sql::set_import_report(&tx, &processing.report)?;
+    sql::analyze(&tx)?;
+    tx.commit()?;
+    Ok(processing.report)
+}
+
+fn read_playlist_lines(
+    playlist: &mut PlaylistLines,
+) -> Result<Vec<(usize, std::io::Result<String>)>> {
+    let mut lines = Vec::new();
+    for (count, line) in playlist.enumerate() {
+        match line {
+            Err(e) if e.kind() != ErrorKind::InvalidData => {
+                return Err(e).with_context(|| format!("Failed to read line {count}"));
+            }
+            line => lines.push((count, line)),
+        }
+    }
+    Ok(lines)
+}
+
+fn expand_nested_playlist(
+    nested: NestedPlaylist,
+    processing: &mut M3UProcessing,
+    tx: &Transaction,
+) -> Result<()> {
+    let base = std::mem::replace(&mut processing.base, nested.base);
+    let line_count = processing.line_count;
+    let channel_line_count = processing.channel_line_count;
+    let result = process_lines(nested.lines.into_iter().map(Ok).enumerate(), processing, tx);
+    try_commit_channel(processing, tx);
+    processing.base = base;
+    processing.line_count = line_count;
+    processing.channel_line_count = channel_line_count;
+    result
+}
+
+fn process_lines(
+    mut lines: impl Iterator<Item = (usize, std::io::Result<String>)>,
+    processing: &mut M3UProcessing,
+    tx: &Transaction,
+) -> Result<()> {
     while let Some((c1, l1)) = lines.next() {
         processing.line_count = c1;
-        let l1 = match l1.with_context(|| format!("Failed to process line {c1}")) {
+        let l1 = match l1 {
             Ok(r) => r,
+            Err(e) if e.kind() != ErrorKind::InvalidData => {
+                return Err(e).with_context(|| format!("Failed to read line {c1}"));
+            }
             Err(e) => {
-                tracing::error!("{:?}", e);
+                processing.report.add_warning(
+                    Some(c1 as i64 + 1),
+                    anyhow::Error::from(e).context(format!("Failed to process line {c1}")),
+                );
                 continue;
             }
         };
         let l1_upper = l1.to_uppercase();
-        if l1_upper.starts_with("#EXTINF") {
-            try_commit_channel(&mut processing, &tx);
+        if l1_upper.starts_with("#EXTM3U") {
+            processing.epg_urls.extend(get_epg_urls(&l1));
+            processing.tvg_shift = processing.tvg_shift.or_else(|| get_tvg_shift(&l1));
+        } else if l1_upper.starts_with("#EXTINF") {
+            try_commit_channel(processing, tx);
             processing.channel_line = Some(l1);
+            processing.channel_line_count = c1;
             processing.channel_headers_set = false;
         } else if l1_upper.starts_with("#EXTVLCOPT") {
             if processing.channel_headers.is_none() {
@@ -103,16 +570,19 @@ pub fn read_m3u8(mut source: Source, wipe: bool) -> Result<()> {
             ) {
                 processing.channel_headers_set = true;
             }
+        } else if l1_upper.starts_with("#EXTHTTP") {
+            if set_exthttp_headers(
+                &l1,
+                processing
+                    .channel_headers
+                    .get_or_insert_with(ChannelHttpHeaders::default),
+            ) {
+                processing.channel_headers_set = true;
+            }
         } else if !l1.trim().is_empty() {
             processing.last_non_empty_line = Some(l1);
         }
     }
-    try_commit_channel(&mut processing, &tx);
-    if wipe {
-        sql::restore_preserve(&tx, source.id.context("no source id")?, channel_preserve)?;
-    }
-    sql::analyze(&tx)?;
-    tx.commit()?;
     Ok(())
 }
 
@@ -121,57 +591,186 @@ fn try_commit_channel(processing: &mut M3UProcessing, tx: &Transaction) {
         if !processing.channel_headers_set {
             processing.channel_headers = None;
         }
-        commit_channel(
-            channel,
-            processing.last_non_empty_line.take(),
-            &mut processing.groups,
-            processing.channel_headers.take(),
-            processing.source_id,
-            &tx,
-        )
-        .with_context(|| {
+        let last_line = processing.last_non_empty_line.take();
+        let headers = processing.channel_headers.take();
+        match commit_channel(channel, last_line, headers, processing, &tx).with_context(|| {
             format!(
                 "Failed to process channel ending at line {}",
                 processing.line_count
             )
-        })
-        .unwrap_or_else(|e| {
-            tracing::error!("{:?}", e);
-        });
+        }) {
+            Ok(count) => processing.report.imported += count,
+            Err(e) => processing
+                .report
+                .add_skipped(Some(processing.channel_line_count as i64 + 1), e),
+        }
+        processing.position += 1;
     }
 }
 
 fn commit_channel(
     channel_line: String,
     last_line: Option<String>,
-    groups: &mut HashMap<String, i64>,
     headers: Option<ChannelHttpHeaders>,
-    source_id: i64,
+    processing: &mut M3UProcessing,
     tx: &Transaction,
-) -> Result<()> {
-    let mut channel = get_channel_from_lines(
-        channel_line,
-        last_line.context("missing last line")?,
-        source_id,
-    )?;
-    set_channel_group_id(groups, &mut channel, tx, &source_id).unwrap_or_else(|e| {
-        tracing::error!(
-            "Failed to set group id for channel: {}, Error: {:?}",
-            channel.name,
-            e
-        )
-    });
-    sql::insert_channel(tx, channel)?;
+) -> Result<i64> {
+    let source_id = processing.source_id;
+    let mut attributes = parse_attributes(&channel_line);
+    let (url, pipe_headers) = get_pipe_headers(&last_line.context("missing URL")?);
+    let url = resolve_url(url, &processing.base);
+    if processing.expanded.contains(&url) {
+        processing.report.add_warning(
+            Some(processing.channel_line_count as i64 + 1),
+            anyhow!("Skipped nested playlist {url} that was already imported"),
+        );
+        return Ok(0);
+    }
+    match processing.nested.remove(&url) {
+        Some(Ok(nested)) => {
+            processing.expanded.insert(url);
+            expand_nested_playlist(nested, processing, tx)?;
+            return Ok(0);
+        }
+        None => {}
+        Some(Err(e)) => processing.report.add_warning(
+            Some(processing.channel_line_count as i64 + 1),
+            e.context(format!("Failed to expand nested playlist {url}")),
+        ),
+    }
+    let headers = merge_http_headers(headers, pipe_headers);
+    let mut channel = get_channel_from_attributes(&mut attributes, url, source_id)?;
+    channel.position = Some(processing.position);
+    match get_episode_info(&channel) {
+        Some(episode) => set_episode_series(&mut channel, episode, processing, tx)?,
+        None => set_group_id(&mut channel, processing, tx),
+    }
+    set_vod_metadata(&mut channel, &mut attributes);
+    let channel_id = sql::insert_channel(tx, channel)?;
+    sql::insert_channel_attributes(tx, channel_id, &attributes.attributes)?;
     if let Some(mut headers) = headers {
-        headers.channel_id = Some(tx.last_insert_rowid());
+        headers.channel_id = Some(channel_id);
         sql::insert_channel_headers(tx, headers)?;
     }
+    Ok(1)
+}
+
+fn set_group_id(channel: &mut Channel, processing: &mut M3UProcessing, tx: &Transaction) {
+    let group_position = processing.groups.len() as i64;
+    set_channel_group_id(
+        &mut processing.groups,
+        channel,
+        tx,
+        &processing.source_id,
+        group_position,
+    )
+    .unwrap_or_else(|e| {
+        processing.report.add_warning(
+            Some(processing.channel_line_count as i64 + 1),
+            e.context(format!(
+                "Failed to set group id for channel: {}",
+                channel.name
+            )),
+        )
+    });
+}
+
+fn set_episode_series(
+    channel: &mut Channel,
+    episode: EpisodeInfo,
+    processing: &mut M3UProcessing,
+    tx: &Transaction,
+) -> Result<()> {
+    let series_id = get_series_id(processing.source_id, &episode.series_name);
+    if processing.series.insert(series_id) {
+        let mut series = Channel {
+            name: episode.series_name,
+            url: Some(series_id.to_string()),
+            media_type: media_type::SERIE,
+            tv_archive: None,
+            tv_archive_duration: None,
+            catchup_type: None,
+            catchup_source: None,
+            channel_number: None,
+            tvg_id: None,
+            duration: None,
+            ..channel.clone()
+        };
+        set_group_id(&mut series, processing, tx);
+        sql::insert_channel(tx, series)?;
+    }
+    let season_id = sql::insert_season(
+        tx,
+        Season {
+            name: format!("Season {}", episode.season_number),
+            season_number: episode.season_number,
+            series_id,
+            source_id: processing.source_id,
+            ..Default::default()
+        },
+    )?;
+    channel.media_type = media_type::MOVIE;
+    channel.series_id = Some(series_id);
+    channel.season_id = Some(season_id);
+    channel.episode_num = Some(episode.episode_num);
     Ok(())
 }
 
-pub async fn get_m3u8_from_link(source: Source, wipe: bool) -> Result<()> {
-    let user_agent = get_user_agent_from_source(&source)?;
-    let client = reqwest::Client::builder().user_agent(user_agent).build()?;
+fn set_vod_metadata(channel: &mut Channel, attributes: &mut M3UAttributes) {
+    if channel.media_type != media_type::MOVIE {
+        return;
+    }
+    channel.duration = attributes.duration;
+    channel.language = attributes.take("tvg-language");
+    channel.country = attributes.take("tvg-country");
+}
+
+fn get_series_id(source_id: i64, series_name: &str) -> i64 {
+    let hash = format!("{source_id}:{}", series_name.to_lowercase())
+        .bytes()
+        .fold(FNV_OFFSET_BASIS, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+        });
+    (hash & MAX_SERIES_ID) as i64
+}
+
+fn get_episode_info(channel: &Channel) -> Option<EpisodeInfo> {
+    let url = channel.url.as_deref().unwrap_or_default();
+    let path = get_url_path(url);
+    if channel.media_type == media_type::LIVESTREAM && !path.contains("/series/") {
+        return None;
+    }
+    parse_episode(&channel.name).or_else(|| {
+        let file_name = Path::new(&path).file_stem()?.to_string_lossy().to_string();
+        parse_episode(
+            &percent_decode_str(&file_name)
+                .decode_utf8_lossy()
+                .replace(['.', '_'], " "),
+        )
+    })
+}
+
+fn parse_episode(text: &str) -> Option<EpisodeInfo> {
+    EPISODE_REGEXES.iter().find_map(|regex| {
+        let caps = regex.captures(text)?;
+        let series_name = text[..caps.get(0)?.start()]
+            .trim_end_matches(|c: char| c.is_whitespace() || "-_.:|([".contains(c))
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" ");
+        if series_name.is_empty() {
+            return None;
+        }
+        Some(EpisodeInfo {
+            series_name,
+            season_number: caps["season"].parse().ok()?,
+            episode_num: caps["episode"].parse().ok()?,
+        })
+    })
+}
+
+pub async fn get_m3u8_from_link(source: Source, wipe: bool) -> Result<ImportReport> {
+    let client = get_http_client(&source)?;
     let url = source.url.clone().context("Invalid source")?;
     let mut response = client.get(&url).send().await?;
     if !response.status().is_success() {
@@ -184,20 +783,29 @@ pub async fn get_m3u8_from_link(source: Source, wipe: bool) -> Result<()> {
             response.status()
         );
     }
-    let mut file = std::fs::File::create(get_tmp_path()?)?;
-    while let Some(chunk) = response.chunk().await? {
-        file.write_all(&chunk)?;
-    }
-    read_m3u8(source, wipe)
-}
-
-fn get_tmp_path() -> Result<String> {
-    let mut path = PathBuf::from_str(utils::TEMP_PATH.get().context("no temp path")?)?;
-    if !path.exists() {
-        std::fs::create_dir_all(&path)?;
+    let hint = get_response_hint(&response);
+    let base = PlaylistBase::Url(response.url().clone());
+    let (sender, receiver) = tokio::sync::mpsc::channel(32);
+    let parser = tokio::task::spawn_blocking(move || {
+        let reader = ChunkReader {
+            receiver,
+            chunk: Cursor::new(Vec::new()),
+        };
+        read_m3u8_from_reader(open_playlist(reader, &hint)?, source, wipe, base)
+    });
+    loop {
+        let chunk = match response.chunk().await {
+            Ok(Some(chunk)) => Ok(chunk.into()),
+            Ok(None) => break,
+            Err(e) => Err(std::io::Error::other(e)),
+        };
+        let failed = chunk.is_err();
+        if sender.send(chunk).await.is_err() || failed {
+            break;
+        }
     }
-    path.push("get.m3u");
-    Ok(path.to_string_lossy().to_string())
+    drop(sender);
+    parser.await?
 }
 
 fn extract_non_empty_capture(caps: Captures) -> Option<String> {
@@ -206,6 +814,158 @@ fn extract_non_empty_capture(caps: Captures) -> Option<String> {
         .filter(|s| !s.trim().is_empty())
 }
 
+fn get_extinf_duration(directive: &str) -> Option<i64> {
+    directive
+        .split_once(':')?
+        .1
+        .parse::<f64>()
+        .ok()
+        .filter(|duration| *duration > 0.0)
+        .map(|duration| duration.round() as i64)
+}
+
+fn parse_attributes(line: &str) -> M3UAttributes {
+    let mut result = M3UAttributes::default();
+    let mut chars = line.char_indices().peekable();
+    let mut directive = String::new();
+    while let Some((_, c)) = chars.next_if(|(_, c)| !c.is_whitespace() && *c != ',') {
+        directive.push(c);
+    }
+    result.duration = get_extinf_duration(&directive);
+    loop {
+        while chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+        let Some((start, c)) = chars.next() else {
+            break;
+        };
+        if c == ',' {
+            result.title = Some(line[start + 1..].trim().to_string()).filter(|t| !t.is_empty());
+            break;
+        }
+        let mut key = String::from(c);
+        while let Some((_, c)) =
+            chars.next_if(|(_, c)| !c.is_whitespace() && *c != '=' && *c != ',')
+        {
+            key.push(c);
+        }
+        if chars.next_if(|(_, c)| *c == '=').is_none() {
+            continue;
+        }
+        let mut value = String::new();
+        match chars.next_if(|(_, c)| *c == '"' || *c == '\'') {
+            Some((_, quote)) => {
+                for (_, c) in chars.by_ref() {
+                    if c == quote {
+                        break;
+                    }
+                    value.push(c);
+                }
+            }
+            None => {
+                while let Some((_, c)) = chars.next_if(|(_, c)| !c.is_whitespace() && *c != ',') {
+                    value.push(c);
+                }
+            }
+        }
+        result
+            .attributes
+            .entry(key.to_lowercase())
+            .or_insert_with(|| value.trim().to_string());
+    }
+    result
+}
+
+fn get_epg_urls(header: &str) -> Vec<String> {
+    let mut attributes = parse_attributes(header);
+    ["url-tvg", "x-tvg-url"]
+        .iter()
+        .filter_map(|key| attributes.take(key))
+        .flat_map(|urls| {
+            urls.split(',')
+                .map(|url| url.trim().to_string())
+                .filter(|url| !url.is_empty())
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+fn get_tvg_shift(header: &str) -> Option<f64> {
+    parse_attributes(header)
+        .take("tvg-shift")
+        .and_then(|shift| shift.parse().ok())
+}
+
+fn get_pipe_headers(url: &str) -> (String, Option<ChannelHttpHeaders>) {
+    let Some((url, options)) = url.split_once('|') else {
+        return (url.to_string(), None);
+    };
+    let mut headers = ChannelHttpHeaders::default();
+    let mut headers_set = false;
+    for (key, value) in form_urlencoded::parse(options.trim().as_bytes()) {
+        headers_set |= set_http_header(&mut headers, &key, &value);
+    }
+    (url.trim().to_string(), headers_set.then_some(headers))
+}
+
+fn set_exthttp_headers(line: &str, headers: &mut ChannelHttpHeaders) -> bool {
+    let Some((_, json)) = line.split_once(':') else {
+        return false;
+    };
+    let values = match serde_json::from_str::<HashMap<String, serde_json::Value>>(json.trim()) {
+        Ok(values) => values,
+        Err(e) => {
+            tracing::error!("Failed to parse EXTHTTP headers: {:?}", e);
+            return false;
+        }
+    };
+    let mut headers_set = false;
+    for (key, value) in values {
+        let value = match value {
+            serde_json::Value::String(value) => value,
+            value => value.to_string(),
+        };
+        headers_set |= set_http_header(headers, &key, &value);
+    }
+    headers_set
+}
+
+fn set_http_header(headers: &mut ChannelHttpHeaders, key: &str, value: &str) -> bool {
+    let key = key.trim();
+    let value = value.trim();
+    if key.is_empty() || value.is_empty() {
+        return false;
+    }
+    let value = value.to_string();
+    match key.to_lowercase().as_str() {
+        "user-agent" => headers.user_agent = Some(value),
+        "referer" | "referrer" => headers.referrer = Some(value),
+        "origin" => headers.http_origin = Some(value),
+        "cookie" => headers.cookie = Some(value),
+        _ => {
+            headers.extra_headers.insert(key.to_string(), value);
+        }
+    }
+    true
+}
+
+fn merge_http_headers(
+    headers: Option<ChannelHttpHeaders>,
+    other: Option<ChannelHttpHeaders>,
+) -> Option<ChannelHttpHeaders> {
+    match (headers, other) {
+        (Some(mut headers), Some(other)) => {
+            headers.user_agent = headers.user_agent.or(other.user_agent);
+            headers.referrer = headers.referrer.or(other.referrer);
+            headers.http_origin = headers.http_origin.or(other.http_origin);
+            headers.cookie = headers.cookie.or(other.cookie);
+            for (key, value) in other.extra_headers {
+                headers.extra_headers.entry(key).or_insert(value);
+            }
+            Some(headers)
+        }
+        (headers, other) => headers.or(other),
+    }
+}
+
 fn set_http_headers(line: &str, headers: &mut ChannelHttpHeaders) -> bool {
     if let Some(origin) = HTTP_ORIGIN_REGEX
         .captures(&line)
@@ -229,66 +989,93 @@ fn set_http_headers(line: &str, headers: &mut ChannelHttpHeaders) -> bool {
     return false;
 }
 
-fn get_channel_from_lines(first: String, mut second: String, source_id: i64) -> Result<Channel> {
+#[cfg(test)]
+fn get_channel_from_lines(first: String, second: String, source_id: i64) -> Result<Channel> {
+    get_channel_from_attributes(&mut parse_attributes(&first), second, source_id)
+}
+
+fn get_channel_from_attributes(
+    attributes: &mut M3UAttributes,
+    mut second: String,
+    source_id: i64,
+) -> Result<Channel> {
     second = second.trim().to_string();
     if second.is_empty() {
-        bail!("second line is empty");
+        bail!("missing URL");
     }
-    let name = NAME_REGEX
-        .captures(&first)
-        .and_then(extract_non_empty_capture)
-        .or_else(|| {
-            let id = || {
-                ID_REGEX
-                    .captures(&first)
-                    .and_then(extract_non_empty_capture)
-            };
-            let name_alt = || {
-                NAME_REGEX_ALT
-                    .captures(&first)
-                    .and_then(extract_non_empty_capture)
-            };
-            return name_alt().or(id());
-        })
-        .context("Couldn't find name from Name or ID")?;
-    let group = GROUP_REGEX
-        .captures(&first)
-        .and_then(extract_non_empty_capture);
-    let image = LOGO_REGEX
-        .captures(&first)
-        .and_then(extract_non_empty_capture);
+    let tvg_id = attributes.take("tvg-id");
+    let name = attributes
+        .take("tvg-name")
+        .or_else(|| attributes.title.take())
+        .or(tvg_id.clone())
+        .context("missing name")?;
+    let group = attributes.take("group-title");
+    let image = attributes.take("tvg-logo");
+    let catchup_type = attributes
+        .take_any(&["catchup", "catchup-type"])
+        .map(|x| x.to_lowercase());
+    let catchup_source = attributes.take("catchup-source");
+    let catchup_days = attributes
+        .take_any(&["catchup-days", "timeshift", "tvg-rec"])
+        .and_then(|x| x.parse().ok());
+    let channel_number = attributes.take("tvg-chno").and_then(|x| x.parse().ok());
+    let tv_archive =
+        catchup::is_supported_catchup(catchup_type.as_deref(), catchup_source.as_deref())
+            .then_some(true);
     let channel = Channel {
         id: None,
         name: name.trim().to_string(),
         group: group.map(|x| x.trim().to_string()),
         image: image.map(|x| x.trim().to_string()),
         url: Some(second.clone()),
-        media_type: get_media_type(second),
+        media_type: get_media_type(&second),
         source_id: Some(source_id),
         series_id: None,
         group_id: None,
         favorite: false,
         stream_id: None,
-        tv_archive: None,
+        tv_archive,
+        tv_archive_duration: catchup_days,
+        catchup_type,
+        catchup_source,
+        channel_number,
+        position: None,
         season_id: None,
         episode_num: None,
+        tvg_id,
+        duration: None,
+        language: None,
+        country: None,
+        epg_now: None,
+        epg_next: None,
     };
     Ok(channel)
 }
 
-fn get_media_type(url: String) -> u8 {
-    let media_type = if url.ends_with(".mp4") || url.ends_with(".mkv") {
-        media_type::MOVIE
-    } else {
-        media_type::LIVESTREAM
-    };
-    return media_type;
+fn get_media_type(url: &str) -> u8 {
+    let is_vod = Path::new(&get_url_path(url))
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .is_some_and(|extension| VOD_EXTENSIONS.contains(&extension.as_str()));
+    match is_vod {
+        true => media_type::MOVIE,
+        false => media_type::LIVESTREAM,
+    }
 }
 
 #[cfg(test)]
 mod test_m3u {
 
-    use crate::m3u::get_channel_from_lines;
+    use std::io::{BufRead, Read, Write};
+
+    use crate::m3u::{
+        EpisodeInfo, MAX_NESTED_PLAYLIST_SIZE, PlaylistBase, PlaylistFormat, PlaylistLines,
+        SizeLimitedReader, get_channel_from_lines, get_epg_urls, get_episode_info, get_media_type,
+        get_pipe_headers, get_playlist_format, get_series_id, get_tvg_shift, is_nested_playlist,
+        open_playlist, parse_attributes, resolve_url, set_exthttp_headers,
+    };
+    use crate::media_type;
+    use crate::types::ChannelHttpHeaders;
 
     #[test]
     fn test_get_channel_from_lines() {
@@ -304,4 +1091,274 @@ mod test_m3u {
         assert!(get_channel_from_lines(r#"#EXTINF:-1 tvg-id="Id Of Channel" tvg-name="" tvg-logo="http://myurl.local/amazing/stuff.png" group-title="|EU| FRANCE HEVC",Alt Name Of Channel"#.to_string(), "http://myurl.local/1111/1111.ts".to_string(), 0).unwrap().name == "Id Of Channel");
         assert!(get_channel_from_lines(r#"#EXTINF:-1 tvg-id="Id Of Channel" tvg-name="" tvg-logo="http://myurl.local/amazing/stuff.png" group-title="|EU| FRANCE HEVC",Alt Name Of Channel"#.to_string(), "http://myurl.local/1111/1111.ts".to_string(), 0).unwrap().name == "Alt Name Of Channel");
     }
+
+    #[test]
+    fn test_parse_attributes() {
+        let attributes = parse_attributes(
+            r#"#EXTINF:-1 tvg-id="a.tv" tvg-chno=12 tvg-language='French, English' radio="true" tvg-name="A, the channel",A Title"#,
+        );
+        assert_eq!(attributes.attributes["tvg-id"], "a.tv");
+        assert_eq!(attributes.attributes["tvg-chno"], "12");
+        assert_eq!(attributes.attributes["tvg-language"], "French, English");
+        assert_eq!(attributes.attributes["radio"], "true");
+        assert_eq!(attributes.attributes["tvg-name"], "A, the channel");
+        assert_eq!(attributes.title.as_deref(), Some("A Title"));
+        assert_eq!(attributes.duration, None);
+        let attributes = parse_attributes(r#"#EXTINF:-1,Only A Title"#);
+        assert!(attributes.attributes.is_empty());
+        assert_eq!(attributes.title.as_deref(), Some("Only A Title"));
+        let attributes = parse_attributes(r#"#EXTINF:5420 tvg-rec="3",A Movie"#);
+        assert_eq!(attributes.duration, Some(5420));
+        assert_eq!(attributes.attributes["tvg-rec"], "3");
+        assert_eq!(parse_attributes("#EXTINF:95.6,A Movie").duration, Some(96));
+        assert_eq!(parse_attributes("#EXTINF:0,A Movie").duration, None);
+    }
+
+    #[test]
+    fn test_get_pipe_headers() {
+        let (url, headers) = get_pipe_headers(
+            "http://a.local/stream.m3u8|User-Agent=Mozilla%2F5.0%20(X11)&Referer=http%3A%2F%2Fb.local%2F&X-Other=1",
+        );
+        let headers = headers.unwrap();
+        assert_eq!(url, "http://a.local/stream.m3u8");
+        assert_eq!(headers.user_agent.as_deref(), Some("Mozilla/5.0 (X11)"));
+        assert_eq!(headers.referrer.as_deref(), Some("http://b.local/"));
+        assert_eq!(headers.http_origin, None);
+        let (url, headers) = get_pipe_headers("http://a.local/stream.ts");
+        assert_eq!(url, "http://a.local/stream.ts");
+        assert!(headers.is_none());
+    }
+
+    #[test]
+    fn test_set_exthttp_headers() {
+        let mut headers = ChannelHttpHeaders::default();
+        assert!(set_exthttp_headers(
+            r#"#EXTHTTP:{"cookie":"session=abc","User-Agent":"Foo","Authorization":"Bearer x"}"#,
+            &mut headers
+        ));
+        assert_eq!(headers.cookie.as_deref(), Some("session=abc"));
+        assert_eq!(headers.user_agent.as_deref(), Some("Foo"));
+        assert_eq!(headers.extra_headers["Authorization"], "Bearer x");
+        assert!(!set_exthttp_headers("#EXTHTTP:not json", &mut headers));
+    }
+
+    #[test]
+    fn test_get_epg_urls() {
+        assert_eq!(
+            get_epg_urls(
+                r#"#EXTM3U url-tvg="http://a.local/epg.xml, http://b.local/epg.xml.gz" x-tvg-url="http://c.local/guide.xml""#
+            ),
+            vec![
+                "http://a.local/epg.xml",
+                "http://b.local/epg.xml.gz",
+                "http://c.local/guide.xml"
+            ]
+        );
+        assert!(get_epg_urls("#EXTM3U").is_empty());
+    }
+
+    #[test]
+    fn test_get_tvg_shift() {
+        assert_eq!(get_tvg_shift(r#"#EXTM3U tvg-shift="-2""#), Some(-2.0));
+        assert_eq!(get_tvg_shift("#EXTM3U tvg-shift=1.5"), Some(1.5));
+        assert_eq!(get_tvg_shift("#EXTM3U"), None);
+    }
+
+    #[test]
+    fn test_get_playlist_format() {
+        assert_eq!(
+            get_playlist_format(&[0x1f, 0x8b, 8, 0], ""),
+            PlaylistFormat::Gzip
+        );
+        assert_eq!(get_playlist_format(b"PK\x03\x04", ""), PlaylistFormat::Zip);
+        assert_eq!(
+            get_playlist_format(b"#EXT", "text/plain /get.php"),
+            PlaylistFormat::Plain
+        );
+        assert_eq!(
+            get_playlist_format(b"", "application/x-gzip"),
+            PlaylistFormat::Gzip
+        );
+        assert_eq!(
+            get_playlist_format(b"", "/list.m3u.gz"),
+            PlaylistFormat::Gzip
+        );
+        assert_eq!(
+            get_playlist_format(b"", "application/zip"),
+            PlaylistFormat::Zip
+        );
+        assert_eq!(
+            get_playlist_format(b"#EXT", "application/zip /zipped/get.php"),
+            PlaylistFormat::Plain
+        );
+        assert_eq!(
+            get_playlist_format(b"#EXT", "/list.m3u.gz"),
+            PlaylistFormat::Plain
+        );
+    }
+
+    #[test]
+    fn test_open_playlist() {
+        let playlist = "#EXTM3U\n#EXTINF:-1,Channel\nhttp://host/1.ts\n";
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(playlist.as_bytes()).unwrap();
+        let gzip = encoder.finish().unwrap();
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        zip.start_file("list.m3u", zip::write::SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(playlist.as_bytes()).unwrap();
+        let zip = zip.finish().unwrap().into_inner();
+        for data in [playlist.as_bytes().to_vec(), gzip, zip] {
+            let lines: Vec<String> =
+                open_playlist(std::io::Cursor::new(data), "application/zip /list.gz")
+                    .unwrap()
+                    .lines()
+                    .map(Result::unwrap)
+                    .collect();
+            assert_eq!(
+                lines,
+                vec!["#EXTM3U", "#EXTINF:-1,Channel", "http://host/1.ts"]
+            );
+        }
+    }
+
+    #[test]
+    fn test_resolve_url() {
+        let base = PlaylistBase::Url(reqwest::Url::parse("http://host/lists/main.m3u").unwrap());
+        let resolve = |url: &str| resolve_url(url.to_string(), &base);
+        assert_eq!(resolve("../live/1.m3u8"), "http://host/live/1.m3u8");
+        assert_eq!(resolve("/hls/abc.ts"), "http://host/hls/abc.ts");
+        assert_eq!(resolve(" x.ts "), "http://host/lists/x.ts");
+        assert_eq!(resolve("//cdn/z.ts"), "http://cdn/z.ts");
+        assert_eq!(resolve("rtmp://other/y"), "rtmp://other/y");
+        assert_eq!(resolve(""), "");
+        let base = PlaylistBase::Directory(std::path::PathBuf::from("/media/lists"));
+        assert_eq!(
+            resolve_url("movie.mkv".to_string(), &base),
+            "/media/lists/movie.mkv"
+        );
+        assert_eq!(resolve_url("/abs.mkv".to_string(), &base), "/abs.mkv");
+    }
+
+    #[test]
+    fn test_is_nested_playlist() {
+        let lines = |text: &str| text.lines().map(String::from).collect::<Vec<_>>();
+        assert!(is_nested_playlist(&lines("#EXTM3U\n#EXTINF:-1,A\na.ts")));
+        assert!(!is_nested_playlist(&lines(
+            "#EXTM3U\n#EXT-X-TARGETDURATION:6\n#EXTINF:6,\nseg.ts"
+        )));
+        assert!(!is_nested_playlist(&lines(
+            "#EXTM3U\n#EXT-X-STREAM-INF:BANDWIDTH=1\nlow.m3u8"
+        )));
+    }
+
+    #[test]
+    fn test_size_limited_reader() {
+        let mut data = Vec::new();
+        SizeLimitedReader::new(std::io::Cursor::new(vec![b'a'; 10]))
+            .read_to_end(&mut data)
+            .unwrap();
+        assert_eq!(data.len(), 10);
+        let size = MAX_NESTED_PLAYLIST_SIZE as usize + 1;
+        assert!(
+            SizeLimitedReader::new(std::io::Cursor::new(vec![b'a'; size]))
+                .read_to_end(&mut Vec::new())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_get_series_id() {
+        assert_eq!(get_series_id(1, "Show Name"), get_series_id(1, "show name"));
+        assert_ne!(get_series_id(1, "Show Name"), get_series_id(2, "Show Name"));
+        assert_ne!(
+            get_series_id(1, "Show Name"),
+            get_series_id(1, "Other Show")
+        );
+        assert_eq!(get_series_id(1, "Show Name"), 2898157065223302);
+    }
+
+    #[test]
+    fn test_get_media_type() {
+        assert_eq!(get_media_type("http://host/movie/1.MKV"), media_type::MOVIE);
+        assert_eq!(
+            get_media_type("http://host/movie/1.avi?token=2"),
+            media_type::MOVIE
+        );
+        assert_eq!(get_media_type("/media/movies/film.mp4"), media_type::MOVIE);
+        assert_eq!(
+            get_media_type("http://host/live/1.ts"),
+            media_type::LIVESTREAM
+        );
+        assert_eq!(get_media_type("http://host/live/1"), media_type::LIVESTREAM);
+    }
+
+    #[test]
+    fn test_get_episode_info() {
+        let episode = |name: &str, url: &str| {
+            let channel =
+                get_channel_from_lines(format!("#EXTINF:-1,{name}"), url.to_string(), 1).unwrap();
+            get_episode_info(&channel)
+        };
+        let expected = |series_name: &str, season_number, episode_num| {
+            Some(EpisodeInfo {
+                series_name: series_name.to_string(),
+                season_number,
+                episode_num,
+            })
+        };
+        assert_eq!(
+            episode("Show Name S02E05", "http://host/1.mkv"),
+            expected("Show Name", 2, 5)
+        );
+        assert_eq!(
+            episode("Mr. Show - s1 e12 - Pilot", "http://host/1.mp4"),
+            expected("Mr. Show", 1, 12)
+        );
+        assert_eq!(
+            episode("Show Name 2x05", "http://host/series/u/p/1.ts"),
+            expected("Show Name", 2, 5)
+        );
+        assert_eq!(
+            episode("Show Name: Season 2, Episode 5", "http://host/1.mkv"),
+            expected("Show Name", 2, 5)
+        );
+        assert_eq!(
+            episode("Pilot", "http://host/Show%20Name/Show.Name.S03E07.mkv"),
+            expected("Show Name", 3, 7)
+        );
+        assert_eq!(episode("Show Name S02E05", "http://host/live/1.ts"), None);
+        assert_eq!(episode("S02E05", "http://host/1.mkv"), None);
+        assert_eq!(episode("Movie 1080p", "http://host/1.mkv"), None);
+    }
+
+    #[test]
+    fn test_playlist_lines() {
+        let read = |data: Vec<u8>| {
+            let mut playlist = PlaylistLines::new(Box::new(std::io::Cursor::new(data))).unwrap();
+            let lines: Vec<String> = playlist.by_ref().map(Result::unwrap).collect();
+            (lines, playlist.encoding.name())
+        };
+        let expected = vec![
+            "#EXTINF:-1,Télé".to_string(),
+            "http://host/1.ts".to_string(),
+        ];
+        assert_eq!(
+            read(b"#EXTINF:-1,T\xe9l\xe9\r\nhttp://host/1.ts".to_vec()),
+            (expected.clone(), "windows-1252")
+        );
+        assert_eq!(
+            read(
+                "\u{feff}#EXTINF:-1,Télé\nhttp://host/1.ts\n"
+                    .as_bytes()
+                    .to_vec()
+            ),
+            (expected.clone(), "UTF-8")
+        );
+        let utf16: Vec<u8> = "\u{feff}#EXTINF:-1,Télé\r\nhttp://host/1.ts"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        assert_eq!(read(utf16), (expected, "UTF-16LE"));
+    }
 }
@@ -12,6 +12,7 @@ pub const FORCE_TV_MODE: &str = "forceTvMode";
 pub const SHOW_LIVESTREAMS: &str = "showLivestreams";
 pub const SHOW_MOVIES: &str = "showMovies";
 pub const SHOW_SERIES: &str = "showSeries";
+pub const EPG_RETENTION_DAYS: &str = "epgRetentionDays";
 
 pub fn get_settings() -> Result<Settings> {
     let map = sql::get_settings()?;
@@ -24,6 +25,7 @@ pub fn get_settings() -> Result<Settings> {
         show_livestreams: map.get(SHOW_LIVESTREAMS).and_then(|s| s.parse().ok()),
         show_movies: map.get(SHOW_MOVIES).and_then(|s| s.parse().ok()),
         show_series: map.get(SHOW_SERIES).and_then(|s| s.parse().ok()),
+        epg_retention_days: map.get(EPG_RETENTION_DAYS).and_then(|s| s.parse().ok()),
     };
     Ok(settings)
 }
@@ -64,6 +66,12 @@ pub fn update_settings(settings: Settings) -> Result<()> {
     if let Some(show_series) = settings.show_series {
         map.insert(SHOW_SERIES.to_string(), Some(show_series.to_string()));
     }
+    if let Some(epg_retention_days) = settings.epg_retention_days {
+        map.insert(
+            EPG_RETENTION_DAYS.to_string(),
+            Some(epg_retention_days.to_string()),
+        );
+    }
     sql::update_settings(map)?;
     Ok(())
 }
@@ -1,3 +1,4 @@
 pub const ALPHABETICAL_ASC: u8 = 0;
 pub const ALPHABETICAL_DESC: u8 = 1;
 pub const PROVIDER: u8 = 2;
+pub const CHANNEL_NUMBER: u8 = 3;
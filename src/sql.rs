@@ -1,11 +1,15 @@
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::OnceLock;
+use std::time::Duration;
 use std::vec;
 use std::{collections::HashMap, sync::LazyLock};
 
-use crate::sort_type;
-use crate::types::{ChannelPreserve, Season};
+use crate::types::{
+    ChannelPreserve, ChannelProgrammes, EpgChannel, EpgGridQuery, EpgMatch, ImportReport,
+    Programme, Reminder, Season,
+};
+use crate::{epg_match_type, sort_type, source_type};
 use crate::{
     media_type,
     types::{Channel, ChannelHttpHeaders, Filters, Source},
@@ -14,10 +18,11 @@ use crate::{
 use anyhow::{Context, Result, anyhow};
 use r2d2::{Pool, PooledConnection};
 use r2d2_sqlite::SqliteConnectionManager;
-use rusqlite::{OptionalExtension, Row, Transaction, params, params_from_iter};
+use rusqlite::{Connection, OptionalExtension, Row, Transaction, params, params_from_iter};
 use rusqlite_migration::{M, Migrations};
 
 const PAGE_SIZE: u8 = 36;
+const IMPORT_BUSY_TIMEOUT: Duration = Duration::from_secs(60);
 pub const DB_NAME: &str = "db_rust.sqlite";
 pub const LAST_SEEN_VERSION_KEY: &str = "last_seen_version";
 pub static DB_PATH_OVERRIDE: OnceLock<String> = OnceLock::new();
@@ -32,6 +37,12 @@ fn create_connection_pool() -> Pool<SqliteConnectionManager> {
     r2d2::Pool::builder().max_size(20).build(manager).unwrap()
 }
 
+pub fn get_import_conn() -> Result<Connection> {
+    let conn = Connection::open(get_and_create_sqlite_db_path())?;
+    conn.busy_timeout(IMPORT_BUSY_TIMEOUT)?;
+    Ok(conn)
+}
+
 fn get_and_create_sqlite_db_path() -> String {
     let mut path = PathBuf::from_str(DB_PATH_OVERRIDE.get().unwrap()).unwrap();
     if !path.exists() {
@@ -43,8 +54,9 @@ fn get_and_create_sqlite_db_path() -> String {
 
 pub fn apply_migrations() -> Result<()> {
     let mut sql = get_conn()?;
-    let migrations = Migrations::new(vec![M::up(
-        r#"
+    let migrations = Migrations::new(vec![
+        M::up(
+            r#"
 CREATE TABLE "sources" (
   "id"                INTEGER PRIMARY KEY,
   "name"              varchar(100),
@@ -147,7 +159,176 @@ CREATE UNIQUE INDEX unique_seasons ON seasons(season_number, series_id, source_i
 
 ANALYZE;
 "#,
-    )]);
+        ),
+        M::up(
+            r#"
+CREATE TABLE "epg_channels" (
+  "id"           INTEGER PRIMARY KEY,
+  "source_id"    integer,
+  "xmltv_id"     varchar(200),
+  "display_name" varchar(200),
+  "icon"         varchar(500),
+  FOREIGN KEY (source_id) REFERENCES sources(id) ON DELETE CASCADE
+);
+
+CREATE TABLE "programmes" (
+  "id"             INTEGER PRIMARY KEY,
+  "epg_channel_id" integer,
+  "title"          varchar(500),
+  "description"    text,
+  "category"       varchar(200),
+  "icon"           varchar(500),
+  "start_time"     integer,
+  "end_time"       integer,
+  FOREIGN KEY (epg_channel_id) REFERENCES epg_channels(id) ON DELETE CASCADE
+);
+
+ALTER TABLE channels ADD COLUMN tvg_id varchar(200);
+ALTER TABLE channels ADD COLUMN epg_channel_id integer REFERENCES epg_channels(id);
+
+CREATE UNIQUE INDEX index_epg_channels_unique ON epg_channels(xmltv_id, source_id);
+CREATE INDEX index_epg_channels_source_id ON epg_channels(source_id);
+CREATE UNIQUE INDEX index_programmes_unique ON programmes(epg_channel_id, start_time);
+CREATE INDEX index_programmes_end_time ON programmes(end_time);
+CREATE INDEX index_channels_epg_channel_id ON channels(epg_channel_id);
+"#,
+        ),
+        M::up(
+            r#"
+CREATE TABLE "source_epg_urls" (
+  "id"        INTEGER PRIMARY KEY,
+  "source_id" integer,
+  "url"       varchar(500),
+  FOREIGN KEY (source_id) REFERENCES sources(id) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX index_source_epg_urls_unique ON source_epg_urls(source_id, url);
+"#,
+        ),
+        M::up(
+            r#"
+CREATE TABLE "epg_overrides" (
+  "id"           INTEGER PRIMARY KEY,
+  "source_id"    integer,
+  "channel_name" varchar(100),
+  "xmltv_id"     varchar(200),
+  FOREIGN KEY (source_id) REFERENCES sources(id) ON DELETE CASCADE
+);
+
+ALTER TABLE channels ADD COLUMN epg_match_type integer;
+ALTER TABLE channels ADD COLUMN epg_match_confidence integer;
+
+CREATE UNIQUE INDEX index_epg_overrides_unique ON epg_overrides(source_id, channel_name);
+"#,
+        ),
+        M::up(
+            r#"
+CREATE TABLE "reminders" (
+  "id"           INTEGER PRIMARY KEY,
+  "channel_id"   integer,
+  "source_id"    integer,
+  "channel_name" varchar(100),
+  "title"        varchar(500),
+  "start_time"   integer,
+  "end_time"     integer,
+  FOREIGN KEY (source_id) REFERENCES sources(id) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX index_reminders_unique ON reminders(channel_name, source_id, start_time);
+CREATE INDEX index_reminders_start_time ON reminders(start_time);
+"#,
+        ),
+        M::up(
+            r#"
+ALTER TABLE sources ADD COLUMN epg_shift REAL;
+ALTER TABLE sources ADD COLUMN tvg_shift REAL;
+ALTER TABLE channels ADD COLUMN tv_archive_duration integer;
+"#,
+        ),
+        M::up(
+            r#"
+ALTER TABLE channels ADD COLUMN catchup_type varchar(20);
+ALTER TABLE channels ADD COLUMN catchup_source varchar(500);
+"#,
+        ),
+        M::up(
+            r#"
+CREATE TABLE "channel_attributes" (
+  "id"         INTEGER PRIMARY KEY,
+  "channel_id" integer,
+  "key"        varchar(100),
+  "value"      varchar(500),
+  FOREIGN KEY (channel_id) REFERENCES channels(id) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX index_channel_attributes_unique ON channel_attributes(channel_id, key);
+CREATE INDEX index_channel_attributes_key_value ON channel_attributes(key, value);
+"#,
+        ),
+        M::up(
+            r#"
+ALTER TABLE channels ADD COLUMN channel_number integer;
+CREATE INDEX index_channels_channel_number ON channels(channel_number);
+"#,
+        ),
+        M::up(
+            r#"
+ALTER TABLE channels ADD COLUMN position integer;
+ALTER TABLE groups ADD COLUMN position integer;
+ALTER TABLE seasons ADD COLUMN position integer;
+"#,
+        ),
+        M::up(
+            r#"
+ALTER TABLE channel_http_headers ADD COLUMN cookie varchar(2000);
+ALTER TABLE channel_http_headers ADD COLUMN extra_headers text;
+"#,
+        ),
+        M::up(
+            r#"
+ALTER TABLE sources ADD COLUMN ignore_ssl integer DEFAULT 0;
+"#,
+        ),
+        M::up(
+            r#"
+ALTER TABLE sources ADD COLUMN encoding varchar(20);
+"#,
+        ),
+        M::up(
+            r#"
+CREATE TABLE "import_reports" (
+  "id"         INTEGER PRIMARY KEY,
+  "source_id"  integer,
+  "created_at" integer,
+  "imported"   integer,
+  "skipped"    integer,
+  "warnings"   integer,
+  "reasons"    text,
+  "problems"   text,
+  FOREIGN KEY (source_id) REFERENCES sources(id) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX index_import_reports_source_id ON import_reports(source_id);
+"#,
+        ),
+        M::up(
+            r#"
+ALTER TABLE sources ADD COLUMN expand_playlists integer DEFAULT 0;
+"#,
+        ),
+        M::up(
+            r#"
+ALTER TABLE channels ADD COLUMN duration integer;
+ALTER TABLE channels ADD COLUMN language varchar(100);
+ALTER TABLE channels ADD COLUMN country varchar(100);
+"#,
+        ),
+        M::up(
+            r#"
+ALTER TABLE sources ADD COLUMN timezone varchar(100);
+"#,
+        ),
+    ]);
     migrations.to_latest(&mut sql)?;
     Ok(())
 }
@@ -164,8 +345,8 @@ pub fn create_or_find_source_by_name(tx: &Transaction, source: &Source) -> Resul
         return Ok(id);
     }
     tx.execute(
-    "INSERT INTO sources (name, source_type, url, username, password, user_agent, last_updated) VALUES (?, ?, ?, ?, ?, ?, ?)",
-    params![source.name, source.source_type.clone() as u8, source.url, source.username, source.password, source.user_agent, chrono::Utc::now().timestamp()],
+    "INSERT INTO sources (name, source_type, url, username, password, user_agent, last_updated, epg_shift, ignore_ssl, expand_playlists) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    params![source.name, source.source_type.clone() as u8, source.url, source.username, source.password, source.user_agent, chrono::Utc::now().timestamp(), source.epg_shift, source.ignore_ssl, source.expand_playlists],
     )?;
     Ok(tx.last_insert_rowid())
 }
@@ -173,12 +354,13 @@ pub fn create_or_find_source_by_name(tx: &Transaction, source: &Source) -> Resul
 pub fn insert_season(tx: &Transaction, season: Season) -> Result<i64> {
     tx.execute(
         r#"
-        INSERT INTO seasons (name, image, series_id, season_number, source_id)
-        VALUES (?, ?, ?, ?, ?)
+        INSERT INTO seasons (name, image, series_id, season_number, source_id, position)
+        VALUES (?, ?, ?, ?, ?, ?)
         ON CONFLICT (series_id, season_number, source_id)
         DO UPDATE SET
           image = excluded.image,
-          name = excluded.name
+          name = excluded.name,
+          position = excluded.position
         "#,
         params![
             season.name,
@@ -186,6 +368,7 @@ pub fn insert_season(tx: &Transaction, season: Season) -> Result<i64> {
             season.series_id,
             season.season_number,
             season.source_id,
+            season.position,
         ],
     )?;
     Ok(tx.query_row(
@@ -201,11 +384,11 @@ pub fn insert_season(tx: &Transaction, season: Season) -> Result<i64> {
     )?)
 }
 
-pub fn insert_channel(tx: &Transaction, channel: Channel) -> Result<()> {
-    tx.execute(
+pub fn insert_channel(tx: &Transaction, channel: Channel) -> Result<i64> {
+    Ok(tx.query_row(
         r#"
-INSERT INTO channels (name, group_id, image, url, source_id, media_type, series_id, favorite, stream_id, tv_archive, season_id, episode_num)
-VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+INSERT INTO channels (name, group_id, image, url, source_id, media_type, series_id, favorite, stream_id, tv_archive, season_id, episode_num, tvg_id, tv_archive_duration, catchup_type, catchup_source, channel_number, position, duration, language, country)
+VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
 ON CONFLICT (name, source_id, url, series_id, season_id)
 DO UPDATE SET
     url = excluded.url,
@@ -214,7 +397,17 @@ DO UPDATE SET
     image = excluded.image,
     series_id = excluded.series_id,
     tv_archive = excluded.tv_archive,
-    season_id = excluded.season_id;
+    season_id = excluded.season_id,
+    tvg_id = excluded.tvg_id,
+    tv_archive_duration = excluded.tv_archive_duration,
+    catchup_type = excluded.catchup_type,
+    catchup_source = excluded.catchup_source,
+    channel_number = excluded.channel_number,
+    position = excluded.position,
+    duration = excluded.duration,
+    language = excluded.language,
+    country = excluded.country
+RETURNING id;
 "#,
         params![
             channel.name,
@@ -228,24 +421,139 @@ DO UPDATE SET
             channel.stream_id,
             channel.tv_archive,
             channel.season_id,
-            channel.episode_num
+            channel.episode_num,
+            channel.tvg_id,
+            channel.tv_archive_duration,
+            channel.catchup_type,
+            channel.catchup_source,
+            channel.channel_number,
+            channel.position,
+            channel.duration,
+            channel.language,
+            channel.country
         ],
+        |r| r.get(0),
+    )?)
+}
+
+pub fn insert_channel_attributes(
+    tx: &Transaction,
+    channel_id: i64,
+    attributes: &HashMap<String, String>,
+) -> Result<()> {
+    let mut stmt = tx.prepare(
+        r#"
+        INSERT INTO channel_attributes (channel_id, key, value)
+        VALUES (?, ?, ?)
+        ON CONFLICT (channel_id, key)
+        DO UPDATE SET value = excluded.value
+        "#,
     )?;
+    for (key, value) in attributes {
+        stmt.execute(params![channel_id, key, value])?;
+    }
     Ok(())
 }
 
+pub fn get_channels_by_number(number: i64) -> Result<Vec<Channel>> {
+    let sql = get_conn()?;
+    let mut channels: Vec<Channel> = sql
+        .prepare(
+            r#"
+            SELECT c.*
+            FROM channels c
+            JOIN sources s ON s.id = c.source_id
+            WHERE c.channel_number = ?
+            AND c.media_type = ?
+            AND s.enabled = 1
+            ORDER BY c.source_id, c.id
+            "#,
+        )?
+        .query_map(params![number, media_type::LIVESTREAM], row_to_channel)?
+        .filter_map(Result::ok)
+        .collect();
+    set_now_next(&sql, &mut channels)?;
+    Ok(channels)
+}
+
+pub fn get_channel_attributes(channel_id: i64) -> Result<HashMap<String, String>> {
+    let sql = get_conn()?;
+    Ok(sql
+        .prepare("SELECT key, value FROM channel_attributes WHERE channel_id = ?")?
+        .query_map(params![channel_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .filter_map(Result::ok)
+        .collect())
+}
+
+pub fn set_import_report(tx: &Transaction, report: &ImportReport) -> Result<()> {
+    tx.execute(
+        r#"
+INSERT INTO import_reports (source_id, created_at, imported, skipped, warnings, reasons, problems)
+VALUES (?, ?, ?, ?, ?, ?, ?)
+ON CONFLICT(source_id) DO UPDATE SET
+  created_at = excluded.created_at,
+  imported = excluded.imported,
+  skipped = excluded.skipped,
+  warnings = excluded.warnings,
+  reasons = excluded.reasons,
+  problems = excluded.problems
+"#,
+        params![
+            report.source_id,
+            report.created_at,
+            report.imported,
+            report.skipped,
+            report.warnings,
+            serde_json::to_string(&report.reasons)?,
+            serde_json::to_string(&report.problems)?
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn get_import_report(source_id: i64) -> Result<Option<ImportReport>> {
+    let sql = get_conn()?;
+    Ok(sql
+        .query_row(
+            "SELECT * FROM import_reports WHERE source_id = ?",
+            params![source_id],
+            |row| {
+                Ok(ImportReport {
+                    source_id: row.get("source_id")?,
+                    created_at: row.get("created_at")?,
+                    imported: row.get("imported")?,
+                    skipped: row.get("skipped")?,
+                    warnings: row.get("warnings")?,
+                    reasons: row
+                        .get::<_, Option<String>>("reasons")?
+                        .and_then(|reasons| serde_json::from_str(&reasons).ok())
+                        .unwrap_or_default(),
+                    problems: row
+                        .get::<_, Option<String>>("problems")?
+                        .and_then(|problems| serde_json::from_str(&problems).ok())
+                        .unwrap_or_default(),
+                })
+            },
+        )
+        .optional()?)
+}
+
 pub fn insert_channel_headers(tx: &Transaction, headers: ChannelHttpHeaders) -> Result<()> {
     tx.execute(
         r#"
-INSERT OR IGNORE INTO channel_http_headers (channel_id, referrer, user_agent, http_origin, ignore_ssl)
-VALUES (?, ?, ?, ?, ?);
+INSERT OR IGNORE INTO channel_http_headers (channel_id, referrer, user_agent, http_origin, ignore_ssl, cookie, extra_headers)
+VALUES (?, ?, ?, ?, ?, ?, ?);
 "#,
         params![
             headers.channel_id,
             headers.referrer,
             headers.user_agent,
             headers.http_origin,
-            headers.ignore_ssl
+            headers.ignore_ssl,
+            headers.cookie,
+            (!headers.extra_headers.is_empty())
+                .then(|| serde_json::to_string(&headers.extra_headers))
+                .transpose()?
         ],
     )?;
     Ok(())
@@ -257,13 +565,14 @@ fn get_or_insert_group(
     image: &Option<String>,
     source_id: &i64,
     media_type: u8,
+    position: i64,
 ) -> Result<i64> {
     let rows_changed = tx.execute(
         r#"
-        INSERT OR IGNORE INTO groups (name, image, source_id, media_type)
-        VALUES (?, ?, ?, ?);
+        INSERT OR IGNORE INTO groups (name, image, source_id, media_type, position)
+        VALUES (?, ?, ?, ?, ?);
         "#,
-        params![group, &image, source_id, media_type],
+        params![group, &image, source_id, media_type, position],
     )?;
     if rows_changed == 0 {
         return Ok(tx.query_row(
@@ -280,6 +589,7 @@ pub fn set_channel_group_id(
     channel: &mut Channel,
     tx: &Transaction,
     source_id: &i64,
+    position: i64,
 ) -> Result<()> {
     if channel.group.is_none() {
         return Ok(());
@@ -291,6 +601,7 @@ pub fn set_channel_group_id(
             &channel.image,
             source_id,
             channel.media_type,
+            position,
         )?;
         groups.insert(channel.group.clone().unwrap(), id);
         channel.group_id = Some(id);
@@ -306,7 +617,14 @@ pub fn get_channel_headers_by_id(id: i64) -> Result<Option<ChannelHttpHeaders>>
     let sql = get_conn()?;
     let headers = sql
         .query_row(
-            "SELECT * FROM channel_http_headers WHERE channel_id = ?",
+            r#"
+SELECT h.id, c.id AS channel_id, h.http_origin, h.referrer, h.user_agent, h.cookie, h.extra_headers,
+       COALESCE(h.ignore_ssl, 0) OR COALESCE(s.ignore_ssl, 0) AS ignore_ssl
+FROM channels c
+JOIN sources s ON s.id = c.source_id
+LEFT JOIN channel_http_headers h ON h.channel_id = c.id
+WHERE c.id = ? AND (h.id IS NOT NULL OR s.ignore_ssl = 1)
+"#,
             params![id],
             row_to_channel_headers,
         )
@@ -322,6 +640,410 @@ fn row_to_channel_headers(row: &Row) -> Result<ChannelHttpHeaders, rusqlite::Err
         referrer: row.get("referrer")?,
         user_agent: row.get("user_agent")?,
         ignore_ssl: row.get("ignore_ssl")?,
+        cookie: row.get("cookie")?,
+        extra_headers: row
+            .get::<_, Option<String>>("extra_headers")?
+            .and_then(|extra_headers| serde_json::from_str(&extra_headers).ok())
+            .unwrap_or_default(),
+    })
+}
+
+pub fn insert_epg_channel(tx: &Transaction, channel: &EpgChannel) -> Result<i64> {
+    Ok(tx.query_row(
+        r#"
+        INSERT INTO epg_channels (source_id, xmltv_id, display_name, icon)
+        VALUES (?, ?, ?, ?)
+        ON CONFLICT (xmltv_id, source_id)
+        DO UPDATE SET
+          display_name = COALESCE(excluded.display_name, display_name),
+          icon = COALESCE(excluded.icon, icon)
+        RETURNING id
+        "#,
+        params![
+            channel.source_id,
+            channel.xmltv_id,
+            channel.display_name,
+            channel.icon
+        ],
+        |r| r.get(0),
+    )?)
+}
+
+pub fn insert_programme(tx: &Transaction, programme: &Programme) -> Result<()> {
+    tx.execute(
+        r#"
+        INSERT INTO programmes (epg_channel_id, title, description, category, icon, start_time, end_time)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT (epg_channel_id, start_time)
+        DO UPDATE SET
+          title = excluded.title,
+          description = excluded.description,
+          category = excluded.category,
+          icon = excluded.icon,
+          end_time = excluded.end_time
+        "#,
+        params![
+            programme.epg_channel_id,
+            programme.title,
+            programme.description,
+            programme.category,
+            programme.icon,
+            programme.start,
+            programme.end
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn set_source_epg_urls(tx: &Transaction, source_id: i64, urls: &[String]) -> Result<()> {
+    tx.execute(
+        "DELETE FROM source_epg_urls WHERE source_id = ?",
+        params![source_id],
+    )?;
+    for url in urls {
+        tx.execute(
+            "INSERT OR IGNORE INTO source_epg_urls (source_id, url) VALUES (?, ?)",
+            params![source_id, url],
+        )?;
+    }
+    Ok(())
+}
+
+pub fn get_source_epg_urls(source_id: i64) -> Result<Vec<String>> {
+    let sql = get_conn()?;
+    Ok(sql
+        .prepare("SELECT url FROM source_epg_urls WHERE source_id = ? ORDER BY id")?
+        .query_map(params![source_id], |row| row.get(0))?
+        .filter_map(Result::ok)
+        .collect())
+}
+
+pub fn reset_epg_matches(tx: &Transaction, source_id: i64) -> Result<()> {
+    tx.execute(
+        r#"
+        UPDATE channels
+        SET epg_channel_id = NULL, epg_match_type = NULL, epg_match_confidence = NULL
+        WHERE source_id = ?
+        AND epg_match_type IS NOT ?
+        "#,
+        params![source_id, epg_match_type::STREAM],
+    )?;
+    Ok(())
+}
+
+pub fn apply_epg_overrides(tx: &Transaction, source_id: i64) -> Result<()> {
+    tx.execute(
+        r#"
+        UPDATE channels
+        SET epg_channel_id = (
+            SELECT e.id
+            FROM epg_channels e
+            WHERE e.source_id = o.source_id
+            AND e.xmltv_id = o.xmltv_id
+          ),
+          epg_match_type = ?,
+          epg_match_confidence = 100
+        FROM epg_overrides o
+        WHERE o.source_id = channels.source_id
+        AND o.channel_name = channels.name
+        AND channels.source_id = ?
+        "#,
+        params![epg_match_type::MANUAL, source_id],
+    )?;
+    Ok(())
+}
+
+pub fn link_epg_channels(tx: &Transaction, source_id: i64, confidence: u8) -> Result<()> {
+    tx.execute(
+        r#"
+        UPDATE channels
+        SET epg_channel_id = e.id,
+          epg_match_type = ?,
+          epg_match_confidence = ?
+        FROM epg_channels e
+        WHERE e.source_id = channels.source_id
+        AND e.xmltv_id = channels.tvg_id
+        AND channels.source_id = ?
+        AND channels.epg_match_type IS NULL
+        "#,
+        params![epg_match_type::ID, confidence, source_id],
+    )?;
+    Ok(())
+}
+
+pub fn get_epg_channels_by_source(tx: &Transaction, source_id: i64) -> Result<Vec<EpgChannel>> {
+    Ok(tx
+        .prepare("SELECT * FROM epg_channels WHERE source_id = ?")?
+        .query_map(params![source_id], row_to_epg_channel)?
+        .filter_map(Result::ok)
+        .collect())
+}
+
+pub fn get_unmatched_channels(tx: &Transaction, source_id: i64) -> Result<Vec<Channel>> {
+    Ok(tx
+        .prepare(
+            r#"
+            SELECT *
+            FROM channels
+            WHERE source_id = ?
+            AND media_type = ?
+            AND epg_match_type IS NULL
+            "#,
+        )?
+        .query_map(params![source_id, media_type::LIVESTREAM], row_to_channel)?
+        .filter_map(Result::ok)
+        .collect())
+}
+
+pub fn set_epg_match(
+    tx: &Transaction,
+    channel_id: i64,
+    epg_channel_id: i64,
+    match_type: u8,
+    confidence: u8,
+) -> Result<()> {
+    tx.execute(
+        r#"
+        UPDATE channels
+        SET epg_channel_id = ?, epg_match_type = ?, epg_match_confidence = ?
+        WHERE id = ?
+        "#,
+        params![epg_channel_id, match_type, confidence, channel_id],
+    )?;
+    Ok(())
+}
+
+pub fn get_epg_match(channel_id: i64) -> Result<EpgMatch> {
+    let sql = get_conn()?;
+    let (epg_channel_id, match_type, confidence): (Option<i64>, Option<u8>, Option<u8>) = sql
+        .query_row(
+            r#"
+            SELECT epg_channel_id, epg_match_type, epg_match_confidence
+            FROM channels
+            WHERE id = ?
+            "#,
+            params![channel_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )?;
+    let epg_channel = match epg_channel_id {
+        Some(id) => sql
+            .query_row(
+                "SELECT * FROM epg_channels WHERE id = ?",
+                params![id],
+                row_to_epg_channel,
+            )
+            .optional()?,
+        None => None,
+    };
+    Ok(EpgMatch {
+        channel_id,
+        epg_channel,
+        match_type,
+        confidence: confidence.unwrap_or_default(),
+    })
+}
+
+pub fn set_epg_override(
+    tx: &Transaction,
+    source_id: i64,
+    channel_name: &str,
+    xmltv_id: Option<String>,
+) -> Result<()> {
+    tx.execute(
+        r#"
+        INSERT INTO epg_overrides (source_id, channel_name, xmltv_id)
+        VALUES (?, ?, ?)
+        ON CONFLICT (source_id, channel_name)
+        DO UPDATE SET xmltv_id = excluded.xmltv_id
+        "#,
+        params![source_id, channel_name, xmltv_id],
+    )?;
+    Ok(())
+}
+
+pub fn delete_epg_override(tx: &Transaction, source_id: i64, channel_name: &str) -> Result<()> {
+    tx.execute(
+        "DELETE FROM epg_overrides WHERE source_id = ? AND channel_name = ?",
+        params![source_id, channel_name],
+    )?;
+    Ok(())
+}
+
+pub fn search_epg_channels(
+    source_id: i64,
+    query: Option<String>,
+    page: u8,
+) -> Result<Vec<EpgChannel>> {
+    let sql = get_conn()?;
+    let offset: u16 = page.max(1) as u16 * PAGE_SIZE as u16 - PAGE_SIZE as u16;
+    let query = format!("%{}%", query.unwrap_or_default());
+    Ok(sql
+        .prepare(
+            r#"
+            SELECT *
+            FROM epg_channels
+            WHERE source_id = ?
+            AND (display_name LIKE ? OR xmltv_id LIKE ?)
+            ORDER BY display_name, xmltv_id
+            LIMIT ?, ?
+            "#,
+        )?
+        .query_map(
+            params![source_id, query, query, offset, PAGE_SIZE],
+            row_to_epg_channel,
+        )?
+        .filter_map(Result::ok)
+        .collect())
+}
+
+fn row_to_epg_channel(row: &Row) -> Result<EpgChannel, rusqlite::Error> {
+    Ok(EpgChannel {
+        id: row.get("id")?,
+        source_id: row.get("source_id")?,
+        xmltv_id: row.get("xmltv_id")?,
+        display_name: row.get("display_name")?,
+        icon: row.get("icon")?,
+    })
+}
+
+pub fn set_source_tvg_shift(tx: &Transaction, source_id: i64, shift: Option<f64>) -> Result<()> {
+    tx.execute(
+        "UPDATE sources SET tvg_shift = ? WHERE id = ?",
+        params![shift, source_id],
+    )?;
+    Ok(())
+}
+
+pub fn set_source_encoding(tx: &Transaction, source_id: i64, encoding: &str) -> Result<()> {
+    tx.execute(
+        "UPDATE sources SET encoding = ? WHERE id = ?",
+        params![encoding, source_id],
+    )?;
+    Ok(())
+}
+
+pub fn set_source_timezone(tx: &Transaction, source_id: i64, timezone: &str) -> Result<()> {
+    tx.execute(
+        "UPDATE sources SET timezone = ? WHERE id = ?",
+        params![timezone, source_id],
+    )?;
+    Ok(())
+}
+
+pub fn get_source_timezone(source_id: i64) -> Result<Option<String>> {
+    let sql = get_conn()?;
+    Ok(sql.query_row(
+        "SELECT timezone FROM sources WHERE id = ?",
+        params![source_id],
+        |r| r.get(0),
+    )?)
+}
+
+pub fn get_source_epg_shift(source_id: i64) -> Result<f64> {
+    let sql = get_conn()?;
+    Ok(sql.query_row(
+        "SELECT COALESCE(epg_shift, tvg_shift, 0) FROM sources WHERE id = ?",
+        params![source_id],
+        |r| r.get(0),
+    )?)
+}
+
+pub fn purge_programmes(before: i64) -> Result<()> {
+    let sql = get_conn()?;
+    sql.execute("DELETE FROM programmes WHERE end_time < ?", params![before])?;
+    sql.execute("DELETE FROM reminders WHERE end_time < ?", params![before])?;
+    Ok(())
+}
+
+pub fn get_channel_programme_at(channel_id: i64, start: i64) -> Result<Option<Programme>> {
+    let sql = get_conn()?;
+    Ok(sql
+        .query_row(
+            r#"
+            SELECT p.*
+            FROM programmes p
+            JOIN channels c ON c.epg_channel_id = p.epg_channel_id
+            WHERE c.id = ?
+            AND p.start_time = ?
+            "#,
+            params![channel_id, start],
+            row_to_programme,
+        )
+        .optional()?)
+}
+
+pub fn insert_reminder(reminder: &Reminder) -> Result<i64> {
+    let sql = get_conn()?;
+    Ok(sql.query_row(
+        r#"
+        INSERT INTO reminders (channel_id, source_id, channel_name, title, start_time, end_time)
+        VALUES (?, ?, ?, ?, ?, ?)
+        ON CONFLICT (channel_name, source_id, start_time)
+        DO UPDATE SET
+          channel_id = excluded.channel_id,
+          title = excluded.title,
+          end_time = excluded.end_time
+        RETURNING id
+        "#,
+        params![
+            reminder.channel_id,
+            reminder.source_id,
+            reminder.channel_name,
+            reminder.title,
+            reminder.start,
+            reminder.end
+        ],
+        |r| r.get(0),
+    )?)
+}
+
+pub fn delete_reminder(id: i64) -> Result<()> {
+    let sql = get_conn()?;
+    sql.execute("DELETE FROM reminders WHERE id = ?", params![id])?;
+    Ok(())
+}
+
+pub fn get_upcoming_reminders(from: i64, to: i64) -> Result<Vec<Reminder>> {
+    let sql = get_conn()?;
+    Ok(sql
+        .prepare(
+            r#"
+            SELECT r.*
+            FROM reminders r
+            JOIN sources s ON s.id = r.source_id
+            WHERE r.start_time >= ?
+            AND r.start_time <= ?
+            AND s.enabled = 1
+            ORDER BY r.start_time
+            "#,
+        )?
+        .query_map(params![from, to], row_to_reminder)?
+        .filter_map(Result::ok)
+        .collect())
+}
+
+fn row_to_reminder(row: &Row) -> Result<Reminder, rusqlite::Error> {
+    Ok(Reminder {
+        id: row.get("id")?,
+        channel_id: row.get("channel_id")?,
+        source_id: row.get("source_id")?,
+        channel_name: row.get("channel_name")?,
+        title: row.get("title")?,
+        start: row.get("start_time")?,
+        end: row.get("end_time")?,
+    })
+}
+
+fn row_to_programme(row: &Row) -> Result<Programme, rusqlite::Error> {
+    Ok(Programme {
+        id: row.get("id")?,
+        epg_channel_id: row.get("epg_channel_id")?,
+        title: row.get("title")?,
+        description: row.get("description")?,
+        category: row.get("category")?,
+        icon: row.get("icon")?,
+        start: row.get("start_time")?,
+        end: row.get("end_time")?,
     })
 }
 
@@ -357,6 +1079,9 @@ pub fn update_settings(map: HashMap<String, Option<String>>) -> Result<()> {
 }
 
 pub fn search(filters: Filters) -> Result<Vec<Channel>> {
+    if filters.search_programmes {
+        return search_programmes(filters);
+    }
     if filters.view_type == view_type::CATEGORIES
         && filters.group_id.is_none()
         && filters.series_id.is_none()
@@ -394,6 +1119,11 @@ pub fn search(filters: Filters) -> Result<Vec<Channel>> {
     let mut baked_params = 2;
     if filters.view_type == view_type::FAVORITES && filters.series_id.is_none() {
         sql_query += "\nAND favorite = 1";
+    } else if filters.view_type != view_type::HISTORY && filters.series_id.is_none() {
+        sql_query += &format!(
+            "\nAND (series_id IS NULL OR source_id IN (SELECT id FROM sources WHERE source_type = {}))",
+            source_type::XTREAM
+        );
     }
 
     if filters.series_id.is_some() {
@@ -416,7 +1146,11 @@ pub fn search(filters: Filters) -> Result<Vec<Channel>> {
         sql_query += "\nORDER BY last_watched DESC";
     } else if filters.season.is_some() {
         sql_query += &format!("\nORDER BY episode_num {0}, name {0}", order)
-    } else if filters.sort != sort_type::PROVIDER {
+    } else if filters.sort == sort_type::CHANNEL_NUMBER {
+        sql_query += "\nORDER BY channel_number IS NULL, channel_number, name";
+    } else if filters.sort == sort_type::PROVIDER {
+        sql_query += "\nORDER BY source_id, position IS NULL, position, id";
+    } else {
         sql_query += &format!("\nORDER BY name {}", order);
     }
     sql_query += "\nLIMIT ?, ?";
@@ -436,11 +1170,235 @@ pub fn search(filters: Filters) -> Result<Vec<Channel>> {
     }
     params.push(&offset);
     params.push(&PAGE_SIZE);
-    let channels: Vec<Channel> = sql
+    let mut channels: Vec<Channel> = sql
         .prepare(&sql_query)?
         .query_map(params_from_iter(params), row_to_channel)?
         .filter_map(Result::ok)
         .collect();
+    set_now_next(&sql, &mut channels).unwrap_or_else(|e| tracing::error!("{:?}", e));
+    Ok(channels)
+}
+
+fn set_now_next(sql: &Connection, channels: &mut [Channel]) -> Result<()> {
+    let ids: Vec<i64> = channels
+        .iter()
+        .filter(|c| c.media_type == media_type::LIVESTREAM)
+        .filter_map(|c| c.id)
+        .collect();
+    if ids.is_empty() {
+        return Ok(());
+    }
+    let now = chrono::Utc::now().timestamp();
+    let sql_query = format!(
+        r#"
+        SELECT *
+        FROM (
+          SELECT c.id AS channel_id, p.*,
+            ROW_NUMBER() OVER (PARTITION BY c.id ORDER BY p.start_time) AS rank
+          FROM channels c
+          JOIN programmes p ON p.epg_channel_id = c.epg_channel_id
+          WHERE c.id IN ({})
+          AND p.end_time > ?
+        )
+        WHERE rank <= 2
+        ORDER BY channel_id, rank
+        "#,
+        generate_placeholders(ids.len())
+    );
+    let mut params: Vec<&dyn rusqlite::ToSql> = Vec::with_capacity(ids.len() + 1);
+    params.extend(to_to_sql(&ids));
+    params.push(&now);
+    let mut programmes: HashMap<i64, Vec<Programme>> = HashMap::new();
+    sql.prepare(&sql_query)?
+        .query_map(params_from_iter(params), |row| {
+            Ok((row.get::<_, i64>("channel_id")?, row_to_programme(row)?))
+        })?
+        .filter_map(Result::ok)
+        .for_each(|(channel_id, programme)| {
+            programmes.entry(channel_id).or_default().push(programme)
+        });
+    for channel in channels.iter_mut() {
+        let Some(mut list) = channel.id.and_then(|id| programmes.remove(&id)) else {
+            continue;
+        };
+        let next = (list.len() > 1).then(|| list.remove(1));
+        let first = list.remove(0);
+        if first.start <= now {
+            channel.epg_now = Some(first);
+            channel.epg_next = next;
+        } else {
+            channel.epg_next = Some(first);
+        }
+    }
+    Ok(())
+}
+
+pub fn get_epg_grid(query: EpgGridQuery) -> Result<Vec<ChannelProgrammes>> {
+    if query.end <= query.start {
+        return Err(anyhow!("Invalid epg grid window"));
+    }
+    let channels = match query.filters {
+        Some(filters) => search(filters)?,
+        None => {
+            let offset = (query.page.max(1) as usize - 1) * PAGE_SIZE as usize;
+            let ids: Vec<i64> = query
+                .channel_ids
+                .into_iter()
+                .skip(offset)
+                .take(PAGE_SIZE as usize)
+                .collect();
+            get_channels_by_ids(&ids)?
+        }
+    };
+    let ids: Vec<i64> = channels.iter().filter_map(|c| c.id).collect();
+    let mut programmes = get_programmes_in_window(&ids, query.start, query.end)?;
+    Ok(channels
+        .into_iter()
+        .map(|channel| ChannelProgrammes {
+            programmes: channel
+                .id
+                .and_then(|id| programmes.remove(&id))
+                .unwrap_or_default(),
+            channel,
+        })
+        .collect())
+}
+
+pub fn get_channel_by_id(id: i64) -> Result<Channel> {
+    get_channels_by_ids(&[id])?
+        .pop()
+        .context("channel not found")
+}
+
+pub fn get_channel_programmes(channel_id: i64, from: i64) -> Result<Vec<Programme>> {
+    Ok(get_programmes_in_window(&[channel_id], from, i64::MAX)?
+        .remove(&channel_id)
+        .unwrap_or_default())
+}
+
+fn get_channels_by_ids(ids: &[i64]) -> Result<Vec<Channel>> {
+    if ids.is_empty() {
+        return Ok(vec![]);
+    }
+    let sql = get_conn()?;
+    let mut channels: HashMap<i64, Channel> = sql
+        .prepare(&format!(
+            "SELECT * FROM channels WHERE id IN ({})",
+            generate_placeholders(ids.len())
+        ))?
+        .query_map(params_from_iter(ids), row_to_channel)?
+        .filter_map(Result::ok)
+        .filter_map(|c| c.id.map(|id| (id, c)))
+        .collect();
+    Ok(ids.iter().filter_map(|id| channels.remove(id)).collect())
+}
+
+fn get_programmes_in_window(
+    channel_ids: &[i64],
+    start: i64,
+    end: i64,
+) -> Result<HashMap<i64, Vec<Programme>>> {
+    let mut programmes: HashMap<i64, Vec<Programme>> = HashMap::new();
+    if channel_ids.is_empty() {
+        return Ok(programmes);
+    }
+    let sql = get_conn()?;
+    let sql_query = format!(
+        r#"
+        SELECT c.id AS channel_id, p.*
+        FROM channels c
+        JOIN programmes p ON p.epg_channel_id = c.epg_channel_id
+        WHERE c.id IN ({})
+        AND p.end_time > ?
+        AND p.start_time < ?
+        ORDER BY p.start_time
+        "#,
+        generate_placeholders(channel_ids.len())
+    );
+    let mut params: Vec<&dyn rusqlite::ToSql> = Vec::with_capacity(channel_ids.len() + 2);
+    params.extend(to_to_sql(channel_ids));
+    params.push(&start);
+    params.push(&end);
+    sql.prepare(&sql_query)?
+        .query_map(params_from_iter(params), |row| {
+            Ok((row.get::<_, i64>("channel_id")?, row_to_programme(row)?))
+        })?
+        .filter_map(Result::ok)
+        .for_each(|(channel_id, programme)| {
+            programmes.entry(channel_id).or_default().push(programme)
+        });
+    Ok(programmes)
+}
+
+fn search_programmes(filters: Filters) -> Result<Vec<Channel>> {
+    let sql = get_conn()?;
+    let offset: u16 = filters.page as u16 * PAGE_SIZE as u16 - PAGE_SIZE as u16;
+    let now = chrono::Utc::now().timestamp();
+    let query = filters.query.as_deref().unwrap_or_default().trim();
+    if query.is_empty() {
+        return Ok(vec![]);
+    }
+    let keywords: Vec<String> = match filters.use_keywords {
+        true => query.split_whitespace().map(|f| format!("%{f}%")).collect(),
+        false => vec![format!("%{query}%")],
+    };
+    let sql_query = format!(
+        r#"
+        SELECT c.*,
+          p.id AS programme_id,
+          p.title AS programme_title,
+          p.description AS programme_description,
+          p.category AS programme_category,
+          p.icon AS programme_icon,
+          p.start_time AS programme_start_time,
+          p.end_time AS programme_end_time
+        FROM programmes p
+        JOIN channels c ON c.epg_channel_id = p.epg_channel_id
+        JOIN sources s ON s.id = c.source_id
+        WHERE ({})
+        AND p.end_time > ?
+        AND c.source_id IN ({})
+        AND s.enabled = 1
+        AND c.url IS NOT NULL
+        ORDER BY p.start_time, c.name
+        LIMIT ?, ?
+        "#,
+        get_programme_keywords_sql(keywords.len()),
+        generate_placeholders(filters.source_ids.len()),
+    );
+    let mut params: Vec<&dyn rusqlite::ToSql> =
+        Vec::with_capacity(3 + filters.source_ids.len() + keywords.len() * 2);
+    for keyword in keywords.iter() {
+        params.push(keyword);
+        params.push(keyword);
+    }
+    params.push(&now);
+    params.extend(to_to_sql(&filters.source_ids));
+    params.push(&offset);
+    params.push(&PAGE_SIZE);
+    let channels: Vec<Channel> = sql
+        .prepare(&sql_query)?
+        .query_map(params_from_iter(params), |row| {
+            let mut channel = row_to_channel(row)?;
+            let programme = Programme {
+                id: row.get("programme_id")?,
+                epg_channel_id: row.get("epg_channel_id")?,
+                title: row.get("programme_title")?,
+                description: row.get("programme_description")?,
+                category: row.get("programme_category")?,
+                icon: row.get("programme_icon")?,
+                start: row.get("programme_start_time")?,
+                end: row.get("programme_end_time")?,
+            };
+            if programme.start <= now {
+                channel.epg_now = Some(programme);
+            } else {
+                channel.epg_next = Some(programme);
+            }
+            Ok(channel)
+        })?
+        .filter_map(Result::ok)
+        .collect();
     Ok(channels)
 }
 
@@ -469,7 +1427,11 @@ fn search_series(filters: Filters) -> Result<Vec<Channel>> {
         sort_type::ALPHABETICAL_DESC => "DESC",
         _ => "ASC",
     };
-    sql_query += &format!("\nORDER BY season_number {}", order);
+    if filters.sort == sort_type::PROVIDER {
+        sql_query += "\nORDER BY position IS NULL, position, season_number";
+    } else {
+        sql_query += &format!("\nORDER BY season_number {}", order);
+    }
     sql_query += "\nLIMIT ?, ?";
     let mut params: Vec<&dyn rusqlite::ToSql> =
         Vec::with_capacity(2 + filters.source_ids.len() + keywords.len());
@@ -500,8 +1462,19 @@ fn season_row_to_channel(row: &Row) -> std::result::Result<Channel, rusqlite::Er
         source_id: None,
         stream_id: None,
         tv_archive: None,
+        tv_archive_duration: None,
+        catchup_type: None,
+        catchup_source: None,
+        channel_number: None,
+        position: None,
         url: None,
         episode_num: None,
+        tvg_id: None,
+        duration: None,
+        language: None,
+        country: None,
+        epg_now: None,
+        epg_next: None,
     })
 }
 
@@ -516,6 +1489,12 @@ fn get_keywords_sql(size: usize) -> String {
         .join(" AND ")
 }
 
+fn get_programme_keywords_sql(size: usize) -> String {
+    std::iter::repeat_n("(p.title LIKE ? OR p.description LIKE ?)", size)
+        .collect::<Vec<_>>()
+        .join(" AND ")
+}
+
 fn generate_placeholders(size: usize) -> String {
     std::iter::repeat("?")
         .take(size)
@@ -573,6 +1552,8 @@ pub fn search_group(filters: Filters) -> Result<Vec<Channel>> {
             _ => "ASC",
         };
         sql_query += &format!("\nORDER BY name {}", order);
+    } else {
+        sql_query += "\nORDER BY source_id, position IS NULL, position, id";
     }
     sql_query += "\nLIMIT ?, ?";
     params.extend(to_to_sql(&keywords));
@@ -602,8 +1583,19 @@ fn row_to_group(row: &Row) -> std::result::Result<Channel, rusqlite::Error> {
         source_id: row.get("source_id")?,
         stream_id: None,
         tv_archive: None,
+        tv_archive_duration: None,
+        catchup_type: None,
+        catchup_source: None,
+        channel_number: None,
+        position: None,
         season_id: None,
         episode_num: None,
+        tvg_id: None,
+        duration: None,
+        language: None,
+        country: None,
+        epg_now: None,
+        epg_next: None,
     };
     Ok(channel)
 }
@@ -623,12 +1615,30 @@ fn row_to_channel(row: &Row) -> std::result::Result<Channel, rusqlite::Error> {
         group: None,
         stream_id: row.get("stream_id")?,
         tv_archive: row.get("tv_archive")?,
+        tv_archive_duration: row.get("tv_archive_duration")?,
+        catchup_type: row.get("catchup_type")?,
+        catchup_source: row.get("catchup_source")?,
+        channel_number: row.get("channel_number")?,
+        position: row.get("position")?,
         season_id: row.get("season_id")?,
+        tvg_id: row.get("tvg_id")?,
+        duration: row.get("duration")?,
+        language: row.get("language")?,
+        country: row.get("country")?,
+        epg_now: None,
+        epg_next: None,
     };
     Ok(channel)
 }
 
 pub fn delete_channels_by_source(tx: &Transaction, source_id: i64) -> Result<()> {
+    tx.execute(
+        r#"
+        DELETE FROM channel_attributes
+        WHERE channel_id IN (SELECT id FROM channels WHERE source_id = ?)
+    "#,
+        params![source_id],
+    )?;
     tx.execute(
         r#"
         DELETE FROM channels
@@ -663,6 +1673,13 @@ pub fn delete_groups_by_source(tx: &Transaction, source_id: i64) -> Result<()> {
 
 pub fn delete_source(id: i64) -> Result<()> {
     let sql = get_conn()?;
+    sql.execute(
+        r#"
+        DELETE FROM channel_attributes
+        WHERE channel_id IN (SELECT id FROM channels WHERE source_id = ?);
+    "#,
+        params![id],
+    )?;
     sql.execute(
         r#"
         DELETE FROM channels
@@ -684,6 +1701,50 @@ pub fn delete_source(id: i64) -> Result<()> {
     "#,
         params![id],
     )?;
+    sql.execute(
+        r#"
+        DELETE FROM programmes
+        WHERE epg_channel_id IN (
+          SELECT id FROM epg_channels WHERE source_id = ?
+        );
+    "#,
+        params![id],
+    )?;
+    sql.execute(
+        r#"
+        DELETE FROM epg_channels
+        WHERE source_id = ?;
+    "#,
+        params![id],
+    )?;
+    sql.execute(
+        r#"
+        DELETE FROM source_epg_urls
+        WHERE source_id = ?;
+    "#,
+        params![id],
+    )?;
+    sql.execute(
+        r#"
+        DELETE FROM epg_overrides
+        WHERE source_id = ?;
+    "#,
+        params![id],
+    )?;
+    sql.execute(
+        r#"
+        DELETE FROM reminders
+        WHERE source_id = ?;
+    "#,
+        params![id],
+    )?;
+    sql.execute(
+        r#"
+        DELETE FROM import_reports
+        WHERE source_id = ?;
+    "#,
+        params![id],
+    )?;
     let count = sql.execute(
         r#"
         DELETE FROM sources
@@ -759,6 +1820,12 @@ fn row_to_source(row: &Row) -> std::result::Result<Source, rusqlite::Error> {
         user_agent: row.get("user_agent")?,
         stream_user_agent: row.get("stream_user_agent")?,
         last_updated: row.get("last_updated")?,
+        epg_shift: row.get("epg_shift")?,
+        ignore_ssl: row.get::<_, Option<bool>>("ignore_ssl")?.unwrap_or(false),
+        encoding: row.get("encoding")?,
+        expand_playlists: row
+            .get::<_, Option<bool>>("expand_playlists")?
+            .unwrap_or(false),
     })
 }
 
@@ -801,7 +1868,8 @@ pub fn update_source(source: Source) -> Result<()> {
     sql.execute(
         r#"
         UPDATE sources
-        SET username = ?, password = ?, url = ?, user_agent = ?, stream_user_agent = ?
+        SET username = ?, password = ?, url = ?, user_agent = ?, stream_user_agent = ?, epg_shift = ?, ignore_ssl = ?,
+            expand_playlists = ?
         WHERE id = ?"#,
         params![
             source.username,
@@ -809,6 +1877,9 @@ pub fn update_source(source: Source) -> Result<()> {
             source.url,
             source.user_agent,
             source.stream_user_agent,
+            source.epg_shift,
+            source.ignore_ssl,
+            source.expand_playlists,
             source.id
         ],
     )?;
@@ -876,6 +1947,22 @@ pub fn restore_preserve(
             )?;
         }
     }
+    tx.execute(
+        r#"
+          UPDATE reminders
+          SET channel_id = (
+            SELECT id
+            FROM channels
+            WHERE name = reminders.channel_name
+            AND source_id = reminders.source_id
+            AND media_type = ?
+            AND series_id IS NULL
+            LIMIT 1
+          )
+          WHERE source_id = ?
+        "#,
+        params![media_type::LIVESTREAM, source_id],
+    )?;
     Ok(())
 }
 
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
@@ -21,9 +23,31 @@ pub struct Channel {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tv_archive: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub tv_archive_duration: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub catchup_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub catchup_source: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel_number: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub season_id: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub episode_num: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tvg_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub epg_now: Option<Programme>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub epg_next: Option<Programme>,
 }
 
 #[derive(Clone, PartialEq, Debug, Deserialize, Serialize, Default)]
@@ -34,6 +58,7 @@ pub struct Season {
     pub image: Option<String>,
     pub series_id: i64,
     pub source_id: i64,
+    pub position: Option<i64>,
 }
 
 #[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
@@ -54,6 +79,14 @@ pub struct Source {
     pub user_agent: Option<String>,
     pub stream_user_agent: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub epg_shift: Option<f64>,
+    #[serde(default)]
+    pub ignore_ssl: bool,
+    #[serde(default)]
+    pub expand_playlists: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub last_updated: Option<i64>,
 }
 
@@ -67,6 +100,7 @@ pub struct Settings {
     pub show_livestreams: Option<bool>,
     pub show_movies: Option<bool>,
     pub show_series: Option<bool>,
+    pub epg_retention_days: Option<u16>,
 }
 
 #[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
@@ -81,6 +115,7 @@ pub struct Filters {
     pub use_keywords: bool,
     pub sort: u8,
     pub season: Option<i64>,
+    pub search_programmes: bool,
 }
 
 #[derive(Clone, PartialEq, Debug, Deserialize, Serialize, Default)]
@@ -93,6 +128,10 @@ pub struct ChannelHttpHeaders {
     pub user_agent: Option<String>,
     pub http_origin: Option<String>,
     pub ignore_ssl: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cookie: Option<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub extra_headers: HashMap<String, String>,
 }
 
 #[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
@@ -103,3 +142,94 @@ pub struct ChannelPreserve {
     #[serde(default)]
     pub is_group: bool,
 }
+
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize, Default)]
+pub struct EpgChannel {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+    pub source_id: i64,
+    pub xmltv_id: String,
+    pub display_name: Option<String>,
+    pub icon: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize, Default)]
+pub struct Programme {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub epg_channel_id: Option<i64>,
+    pub title: String,
+    pub description: Option<String>,
+    pub category: Option<String>,
+    pub icon: Option<String>,
+    pub start: i64,
+    pub end: i64,
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct EpgGridQuery {
+    pub channel_ids: Vec<i64>,
+    pub filters: Option<Filters>,
+    pub start: i64,
+    pub end: i64,
+    pub page: u8,
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct ChannelProgrammes {
+    pub channel: Channel,
+    pub programmes: Vec<Programme>,
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct EpgMatch {
+    pub channel_id: i64,
+    pub epg_channel: Option<EpgChannel>,
+    pub match_type: Option<u8>,
+    pub confidence: u8,
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize, Default)]
+pub struct Reminder {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+    pub channel_id: Option<i64>,
+    pub source_id: i64,
+    pub channel_name: String,
+    pub title: String,
+    pub start: i64,
+    pub end: i64,
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct ResolvedStream {
+    pub url: String,
+    pub headers: ChannelHttpHeaders,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<i64>,
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize, Default)]
+pub struct ImportProblem {
+    pub line: Option<i64>,
+    pub reason: String,
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize, Default)]
+pub struct ImportReport {
+    pub source_id: i64,
+    pub created_at: i64,
+    pub imported: i64,
+    pub skipped: i64,
+    pub warnings: i64,
+    pub reasons: HashMap<String, i64>,
+    pub problems: Vec<ImportProblem>,
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct CatchupUrl {
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fallback_url: Option<String>,
+}
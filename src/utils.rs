@@ -1,22 +1,35 @@
-use crate::{m3u, source_type, sql, types::Source, xtream};
-use anyhow::{Result, anyhow};
+use crate::{
+    epg, m3u, media_type, source_type, sql,
+    types::{ChannelHttpHeaders, ImportReport, ResolvedStream, Source},
+    xtream,
+};
+use anyhow::{Context, Result, anyhow};
 use std::sync::OnceLock;
+use tokio::sync::Mutex;
 
 const DEFAULT_USER_AGENT: &str = "Fred TV";
 pub static TEMP_PATH: OnceLock<String> = OnceLock::new();
+static IMPORT_LOCK: Mutex<()> = Mutex::const_new(());
 
-pub async fn refresh_source(source: Source) -> Result<()> {
+pub async fn refresh_source(source: Source) -> Result<ImportReport> {
     let id = source.id;
-    match source.source_type {
-        source_type::M3U => m3u::read_m3u8(source, true)?,
+    let import = IMPORT_LOCK.lock().await;
+    let report = match source.source_type {
+        source_type::M3U => {
+            tokio::task::spawn_blocking(move || m3u::read_m3u8(source, true)).await??
+        }
         source_type::M3U_LINK => m3u::get_m3u8_from_link(source, true).await?,
         source_type::XTREAM => xtream::get_xtream(source, true).await?,
         _ => return Err(anyhow!("invalid source_type")),
-    }
+    };
+    drop(import);
     if let Some(id) = id {
+        epg::refresh_epg(id)
+            .await
+            .unwrap_or_else(|e| tracing::error!("{:?}", e));
         sql::update_source_last_updated(id)?;
     }
-    Ok(())
+    Ok(report)
 }
 
 pub async fn refresh_all() -> Result<()> {
@@ -27,13 +40,21 @@ pub async fn refresh_all() -> Result<()> {
     Ok(())
 }
 
-pub async fn process_source(source: Source) -> Result<()> {
-    match source.source_type {
-        source_type::M3U => m3u::read_m3u8(source, false),
-        source_type::M3U_LINK => m3u::get_m3u8_from_link(source, false).await,
-        source_type::XTREAM => xtream::get_xtream(source, false).await,
-        _ => Err(anyhow!("invalid source_type")),
-    }
+pub async fn process_source(source: Source) -> Result<ImportReport> {
+    let import = IMPORT_LOCK.lock().await;
+    let report = match source.source_type {
+        source_type::M3U => {
+            tokio::task::spawn_blocking(move || m3u::read_m3u8(source, false)).await??
+        }
+        source_type::M3U_LINK => m3u::get_m3u8_from_link(source, false).await?,
+        source_type::XTREAM => xtream::get_xtream(source, false).await?,
+        _ => return Err(anyhow!("invalid source_type")),
+    };
+    drop(import);
+    epg::refresh_epg(report.source_id)
+        .await
+        .unwrap_or_else(|e| tracing::error!("{:?}", e));
+    Ok(report)
 }
 
 pub fn get_user_agent_from_source(source: &Source) -> Result<String> {
@@ -45,6 +66,38 @@ pub fn get_user_agent_from_source(source: &Source) -> Result<String> {
     Ok(user_agent.to_string())
 }
 
+pub fn resolve_stream(channel_id: i64) -> Result<ResolvedStream> {
+    let channel = sql::get_channel_by_id(channel_id)?;
+    let url = channel.url.context("channel has no url")?;
+    let source = sql::get_source_from_id(channel.source_id.context("no source id")?)?;
+    let mut headers = sql::get_channel_headers_by_id(channel_id)?.unwrap_or(ChannelHttpHeaders {
+        channel_id: Some(channel_id),
+        ignore_ssl: Some(source.ignore_ssl),
+        ..Default::default()
+    });
+    headers.user_agent = headers
+        .user_agent
+        .filter(|s| !s.trim().is_empty())
+        .or(source.stream_user_agent.filter(|s| !s.trim().is_empty()))
+        .or(Some(DEFAULT_USER_AGENT.to_string()));
+    let position = match channel.media_type {
+        media_type::MOVIE => sql::get_movie_position(channel_id)?,
+        _ => None,
+    };
+    Ok(ResolvedStream {
+        url,
+        headers,
+        position,
+    })
+}
+
+pub fn get_http_client(source: &Source) -> Result<reqwest::Client> {
+    Ok(reqwest::Client::builder()
+        .user_agent(get_user_agent_from_source(source)?)
+        .danger_accept_invalid_certs(source.ignore_ssl)
+        .build()?)
+}
+
 pub fn should_show_whats_new(version: Option<String>) -> Result<bool> {
     Ok(sql::get_whats_new()? != version)
 }
@@ -1,16 +1,25 @@
+use crate::epg;
+use crate::epg_match_type;
 use crate::media_type;
 use crate::sql;
 use crate::sql::insert_season;
+use crate::types::CatchupUrl;
 use crate::types::Channel;
 use crate::types::ChannelPreserve;
+use crate::types::EpgChannel;
+use crate::types::ImportReport;
+use crate::types::Programme;
 use crate::types::Season;
 use crate::types::Source;
-use crate::utils::get_user_agent_from_source;
+use crate::utils::get_http_client;
 use anyhow::anyhow;
 use anyhow::{Context, Result};
+use base64::Engine;
+use chrono::DateTime;
+use chrono_tz::Tz;
 use reqwest::Client;
 use reqwest::Url;
-use rusqlite::Transaction;
+use rusqlite::{Transaction, TransactionBehavior};
 use serde::Deserialize;
 use serde::Serialize;
 use std::collections::HashMap;
@@ -23,13 +32,20 @@ const GET_SERIES_INFO: &str = "get_series_info";
 const GET_SERIES_CATEGORIES: &str = "get_series_categories";
 const GET_LIVE_STREAM_CATEGORIES: &str = "get_live_categories";
 const GET_VOD_CATEGORIES: &str = "get_vod_categories";
+const GET_SIMPLE_DATA_TABLE: &str = "get_simple_data_table";
+const GET_SHORT_EPG: &str = "get_short_epg";
+const XMLTV_PATH: &str = "xmltv.php";
 const LIVE_STREAM_EXTENSION: &str = "ts";
+const TIMESHIFT_PATH: &str = "streaming/timeshift.php";
+const TIMESHIFT_TIME_FORMAT: &str = "%Y-%m-%d:%H-%M";
 const NO_SEASON_NUMBER: i64 = -9999;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 struct XtreamStream {
     #[serde(default)]
     stream_id: serde_json::Value,
+    #[serde(default)]
+    num: serde_json::Value,
     name: Option<String>,
     #[serde(default)]
     category_id: serde_json::Value,
@@ -40,7 +56,23 @@ struct XtreamStream {
     container_extension: Option<String>,
     #[serde(default)]
     tv_archive: serde_json::Value,
+    #[serde(default)]
+    tv_archive_duration: serde_json::Value,
+    #[serde(default)]
+    epg_channel_id: Option<String>,
 }
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct XtreamInfo {
+    #[serde(default)]
+    server_info: XtreamServerInfo,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+struct XtreamServerInfo {
+    #[serde(default)]
+    timezone: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 struct XtreamSeries {
     seasons: Vec<XtreamSeason>,
@@ -82,6 +114,26 @@ struct XtreamCategory {
     category_name: String,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct XtreamEpg {
+    #[serde(default)]
+    epg_listings: Vec<XtreamEpgListing>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct XtreamEpgListing {
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    channel_id: Option<String>,
+    #[serde(default)]
+    start_timestamp: serde_json::Value,
+    #[serde(default)]
+    stop_timestamp: serde_json::Value,
+}
+
 fn build_xtream_url(source: &mut Source) -> Result<Url> {
     let mut url = Url::parse(source.url.as_ref().context("Missing URL")?)?;
     source.url_origin = Some(url.origin().ascii_serialization());
@@ -97,27 +149,24 @@ fn build_xtream_url(source: &mut Source) -> Result<Url> {
     Ok(url)
 }
 
-pub async fn get_xtream(mut source: Source, wipe: bool) -> Result<()> {
+pub async fn get_xtream(mut source: Source, wipe: bool) -> Result<ImportReport> {
     let url = build_xtream_url(&mut source)?;
-    let user_agent = get_user_agent_from_source(&source)?;
-    let (live, live_cats, vods, vods_cats, series, series_cats) = join!(
-        get_xtream_http_data::<Vec<XtreamStream>>(url.clone(), GET_LIVE_STREAMS, &user_agent),
+    let client = get_http_client(&source)?;
+    let (live, live_cats, vods, vods_cats, series, series_cats, server_info) = join!(
+        get_xtream_http_data::<Vec<XtreamStream>>(url.clone(), GET_LIVE_STREAMS, &client),
         get_xtream_http_data::<Vec<XtreamCategory>>(
             url.clone(),
             GET_LIVE_STREAM_CATEGORIES,
-            &user_agent
-        ),
-        get_xtream_http_data::<Vec<XtreamStream>>(url.clone(), GET_VODS, &user_agent),
-        get_xtream_http_data::<Vec<XtreamCategory>>(url.clone(), GET_VOD_CATEGORIES, &user_agent),
-        get_xtream_http_data::<Vec<XtreamStream>>(url.clone(), GET_SERIES, &user_agent),
-        get_xtream_http_data::<Vec<XtreamCategory>>(
-            url.clone(),
-            GET_SERIES_CATEGORIES,
-            &user_agent
+            &client
         ),
+        get_xtream_http_data::<Vec<XtreamStream>>(url.clone(), GET_VODS, &client),
+        get_xtream_http_data::<Vec<XtreamCategory>>(url.clone(), GET_VOD_CATEGORIES, &client),
+        get_xtream_http_data::<Vec<XtreamStream>>(url.clone(), GET_SERIES, &client),
+        get_xtream_http_data::<Vec<XtreamCategory>>(url.clone(), GET_SERIES_CATEGORIES, &client),
+        get_xtream_server_info(url.clone(), &client),
     );
-    let mut sql = sql::get_conn()?;
-    let tx = sql.transaction()?;
+    let mut sql = sql::get_import_conn()?;
+    let tx = sql.transaction_with_behavior(TransactionBehavior::Immediate)?;
     let mut channel_preserve: Vec<ChannelPreserve> = Vec::new();
     if wipe {
         channel_preserve =
@@ -126,25 +175,62 @@ pub async fn get_xtream(mut source: Source, wipe: bool) -> Result<()> {
     } else {
         source.id = Some(sql::create_or_find_source_by_name(&tx, &source)?);
     }
-    let mut fail_count = 0;
-    live.and_then(|live| process_xtream(&tx, live, live_cats?, &source, media_type::LIVESTREAM))
+    let mut report = ImportReport::new(source.id.context("no source id")?);
+    if let Some(timezone) = server_info
         .unwrap_or_else(|e| {
-            tracing::error!("{:?}", e.context("Failed to process live"));
-            fail_count += 1;
-        });
+            tracing::error!("{:?}", e.context("Failed to get server info"));
+            XtreamServerInfo::default()
+        })
+        .timezone
+    {
+        sql::set_source_timezone(&tx, report.source_id, &timezone)?;
+    }
+    let mut position = 0;
+    let mut fail_count = 0;
+    live.and_then(|live| {
+        process_xtream(
+            &tx,
+            live,
+            live_cats?,
+            &source,
+            media_type::LIVESTREAM,
+            &mut position,
+            &mut report,
+        )
+    })
+    .unwrap_or_else(|e| {
+        report.add_warning(None, e.context("Failed to process live"));
+        fail_count += 1;
+    });
     vods.and_then(|vods: Vec<XtreamStream>| {
-        process_xtream(&tx, vods, vods_cats?, &source, media_type::MOVIE)
+        process_xtream(
+            &tx,
+            vods,
+            vods_cats?,
+            &source,
+            media_type::MOVIE,
+            &mut position,
+            &mut report,
+        )
     })
     .unwrap_or_else(|e| {
-        tracing::error!("{:?}", e.context("Failed to process vods"));
+        report.add_warning(None, e.context("Failed to process vods"));
         fail_count += 1;
     });
     series
         .and_then(|series: Vec<XtreamStream>| {
-            process_xtream(&tx, series, series_cats?, &source, media_type::SERIE)
+            process_xtream(
+                &tx,
+                series,
+                series_cats?,
+                &source,
+                media_type::SERIE,
+                &mut position,
+                &mut report,
+            )
         })
         .unwrap_or_else(|e| {
-            tracing::error!("{:?}", e.context("Failed to process series"));
+            report.add_warning(None, e.context("Failed to process series"));
             fail_count += 1;
         });
     if fail_count > 2 {
@@ -154,31 +240,44 @@ pub async fn get_xtream(mut source: Source, wipe: bool) -> Result<()> {
         }
         return Err(anyhow::anyhow!("Too many Xtream requests failed"));
     }
+    epg::match_channels(&tx, source.id.context("no source id")?)?;
     if wipe {
         sql::restore_preserve(&tx, source.id.context("no source id")?, channel_preserve)?;
     }
+    sql::set_import_report(&tx, &report)?;
     sql::analyze(&tx)?;
     tx.commit()?;
-    Ok(())
+    Ok(report)
 }
 
-async fn get_xtream_http_data<T>(mut url: Url, action: &str, user_agent: &String) -> Result<T>
+async fn get_xtream_http_data<T>(mut url: Url, action: &str, client: &Client) -> Result<T>
 where
     T: serde::de::DeserializeOwned,
 {
-    let client = Client::builder().user_agent(user_agent).build()?;
     url.query_pairs_mut().append_pair("action", action);
     let data = client.get(url).send().await?.json::<T>().await?;
     Ok(data)
 }
 
+async fn get_xtream_server_info(url: Url, client: &Client) -> Result<XtreamServerInfo> {
+    let info = client.get(url).send().await?.json::<XtreamInfo>().await?;
+    Ok(info.server_info)
+}
+
 fn process_xtream(
     tx: &Transaction,
     streams: Vec<XtreamStream>,
     cats: Vec<XtreamCategory>,
     source: &Source,
     stream_type: u8,
+    position: &mut i64,
+    report: &mut ImportReport,
 ) -> Result<()> {
+    let cat_positions: HashMap<String, i64> = cats
+        .iter()
+        .enumerate()
+        .map(|(position, f)| (f.category_name.trim().to_string(), position as i64))
+        .collect();
     let cats: HashMap<String, String> = cats
         .into_iter()
         .filter_map(|f| {
@@ -187,21 +286,35 @@ fn process_xtream(
         })
         .collect();
     let mut groups: HashMap<String, i64> = HashMap::new();
-    for live in streams {
+    for (index, live) in streams.into_iter().enumerate() {
+        let line = Some(index as i64 + 1);
         let category_name = get_cat_name(&cats, get_serde_json_string(&live.category_id));
-        convert_xtream_live_to_channel(live, &source, stream_type.clone(), category_name)
-            .and_then(|mut channel| {
-                sql::set_channel_group_id(
-                    &mut groups,
-                    &mut channel,
-                    &tx,
-                    source.id.as_ref().unwrap(),
-                )
-                .unwrap_or_else(|e| tracing::error!("{:?}", e));
-                sql::insert_channel(&tx, channel)?;
-                Ok(())
-            })
-            .unwrap_or_else(|e| tracing::error!("{:?}", e));
+        let result =
+            convert_xtream_live_to_channel(live, &source, stream_type.clone(), category_name)
+                .and_then(|mut channel| {
+                    channel.position = Some(*position);
+                    let group_position = channel
+                        .group
+                        .as_ref()
+                        .and_then(|group| cat_positions.get(group.trim()))
+                        .copied()
+                        .unwrap_or(cat_positions.len() as i64);
+                    sql::set_channel_group_id(
+                        &mut groups,
+                        &mut channel,
+                        &tx,
+                        source.id.as_ref().unwrap(),
+                        group_position,
+                    )
+                    .unwrap_or_else(|e| report.add_warning(line, e));
+                    sql::insert_channel(&tx, channel)?;
+                    Ok(())
+                });
+        match result {
+            Ok(_) => report.imported += 1,
+            Err(e) => report.add_skipped(line, e),
+        }
+        *position += 1;
     }
     Ok(())
 }
@@ -245,8 +358,22 @@ fn convert_xtream_live_to_channel(
         group_id: None,
         series_id: None,
         tv_archive: get_serde_json_i64(&stream.tv_archive).map(|x| x == 1),
+        tv_archive_duration: get_serde_json_i64(&stream.tv_archive_duration),
+        catchup_type: None,
+        catchup_source: None,
+        channel_number: get_serde_json_i64(&stream.num),
+        position: None,
         season_id: None,
         episode_num: None,
+        tvg_id: stream
+            .epg_channel_id
+            .map(|x| x.trim().to_string())
+            .filter(|x| !x.is_empty()),
+        duration: None,
+        language: None,
+        country: None,
+        epg_now: None,
+        epg_next: None,
     })
 }
 
@@ -276,6 +403,183 @@ fn get_url(
     ))
 }
 
+pub fn get_catchup_url(
+    source: Source,
+    stream_id: i64,
+    start: i64,
+    duration_minutes: u32,
+) -> Result<CatchupUrl> {
+    let timezone = match source.id {
+        Some(id) => sql::get_source_timezone(id)?,
+        None => None,
+    };
+    build_catchup_url(
+        source,
+        stream_id,
+        start,
+        duration_minutes,
+        timezone.as_deref(),
+    )
+}
+
+fn build_catchup_url(
+    mut source: Source,
+    stream_id: i64,
+    start: i64,
+    duration_minutes: u32,
+    timezone: Option<&str>,
+) -> Result<CatchupUrl> {
+    build_xtream_url(&mut source)?;
+    let origin = source
+        .url_origin
+        .as_deref()
+        .ok_or_else(|| anyhow::anyhow!("missing url_origin"))?;
+    let username = source
+        .username
+        .as_deref()
+        .ok_or_else(|| anyhow::anyhow!("missing username"))?;
+    let password = source
+        .password
+        .as_deref()
+        .ok_or_else(|| anyhow::anyhow!("missing password"))?;
+    let start = get_timeshift_start(start, timezone)?;
+    let mut fallback_url = Url::parse(origin)?;
+    fallback_url.set_path(TIMESHIFT_PATH);
+    fallback_url
+        .query_pairs_mut()
+        .append_pair("username", username)
+        .append_pair("password", password)
+        .append_pair("stream", &stream_id.to_string())
+        .append_pair("start", &start)
+        .append_pair("duration", &duration_minutes.to_string());
+    Ok(CatchupUrl {
+        url: format!(
+            "{origin}/timeshift/{username}/{password}/{duration_minutes}/{start}/{stream_id}.{LIVE_STREAM_EXTENSION}"
+        ),
+        fallback_url: Some(fallback_url.to_string()),
+    })
+}
+
+pub fn parse_timezone(timezone: Option<&str>) -> Option<Tz> {
+    timezone.and_then(|timezone| {
+        timezone
+            .parse::<Tz>()
+            .inspect_err(|e| tracing::error!("Invalid server timezone {timezone}: {e}"))
+            .ok()
+    })
+}
+
+fn get_timeshift_start(start: i64, timezone: Option<&str>) -> Result<String> {
+    let start = DateTime::from_timestamp(start, 0).context("invalid start time")?;
+    Ok(match parse_timezone(timezone) {
+        Some(timezone) => start
+            .with_timezone(&timezone)
+            .format(TIMESHIFT_TIME_FORMAT)
+            .to_string(),
+        None => start.format(TIMESHIFT_TIME_FORMAT).to_string(),
+    })
+}
+
+pub fn get_xmltv_url(mut source: Source) -> Result<String> {
+    let mut url = build_xtream_url(&mut source)?;
+    url.path_segments_mut()
+        .map_err(|_| anyhow!("Invalid Xtream URL"))?
+        .pop()
+        .push(XMLTV_PATH);
+    Ok(url.to_string())
+}
+
+pub async fn get_short_epg(channel_id: i64) -> Result<Vec<Programme>> {
+    let now = chrono::Utc::now().timestamp();
+    let programmes = sql::get_channel_programmes(channel_id, now)?;
+    if !programmes.is_empty() {
+        return Ok(programmes);
+    }
+    let channel = sql::get_channel_by_id(channel_id)?;
+    let stream_id = channel.stream_id.context("channel has no stream id")?;
+    let mut source = sql::get_source_from_id(channel.source_id.context("no source id")?)?;
+    let mut url = build_xtream_url(&mut source)?;
+    let client = get_http_client(&source)?;
+    url.query_pairs_mut()
+        .append_pair("stream_id", &stream_id.to_string());
+    let epg = match get_xtream_http_data::<XtreamEpg>(url.clone(), GET_SIMPLE_DATA_TABLE, &client)
+        .await
+    {
+        Ok(epg) if !epg.epg_listings.is_empty() => epg,
+        result => {
+            if let Err(e) = result {
+                tracing::error!("{:?}", e.context("Failed to get simple data table"));
+            }
+            get_xtream_http_data::<XtreamEpg>(url, GET_SHORT_EPG, &client).await?
+        }
+    };
+    let xmltv_id = channel
+        .tvg_id
+        .clone()
+        .or_else(|| {
+            epg.epg_listings
+                .iter()
+                .find_map(|listing| listing.channel_id.clone())
+        })
+        .unwrap_or_else(|| format!("stream-{stream_id}"));
+    let shift = epg::get_epg_shift(source.id.context("no source id")?)?;
+    sql::do_tx(|tx| {
+        let epg_channel_id = sql::insert_epg_channel(
+            tx,
+            &EpgChannel {
+                source_id: source.id.context("no source id")?,
+                xmltv_id,
+                display_name: Some(channel.name.clone()),
+                icon: channel.image.clone(),
+                ..Default::default()
+            },
+        )?;
+        for listing in epg.epg_listings {
+            let Some(mut programme) = xtream_listing_to_programme(listing) else {
+                continue;
+            };
+            programme.start += shift;
+            programme.end += shift;
+            programme.epg_channel_id = Some(epg_channel_id);
+            sql::insert_programme(tx, &programme).unwrap_or_else(|e| tracing::error!("{:?}", e));
+        }
+        sql::set_epg_match(
+            tx,
+            channel_id,
+            epg_channel_id,
+            epg_match_type::STREAM,
+            epg::STREAM_MATCH_CONFIDENCE,
+        )
+    })?;
+    sql::get_channel_programmes(channel_id, now)
+}
+
+fn xtream_listing_to_programme(listing: XtreamEpgListing) -> Option<Programme> {
+    Some(Programme {
+        title: listing
+            .title
+            .map(|title| decode_base64(&title))
+            .unwrap_or_default(),
+        description: listing
+            .description
+            .map(|description| decode_base64(&description))
+            .filter(|description| !description.is_empty()),
+        start: get_serde_json_i64(&listing.start_timestamp)?,
+        end: get_serde_json_i64(&listing.stop_timestamp)?,
+        ..Default::default()
+    })
+}
+
+fn decode_base64(value: &str) -> String {
+    base64::engine::general_purpose::STANDARD
+        .decode(value.trim())
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .unwrap_or_else(|| value.trim().to_string())
+        .trim()
+        .to_string()
+}
+
 fn get_media_type_string(stream_type: u8) -> Result<String> {
     match stream_type {
         media_type::LIVESTREAM => Ok("live".to_string()),
@@ -298,23 +602,22 @@ pub async fn get_episodes(
     }
     let mut source = sql::get_source_from_id(source_id)?;
     let mut url = build_xtream_url(&mut source)?;
-    let user_agent = get_user_agent_from_source(&source)?;
+    let client = get_http_client(&source)?;
     url.query_pairs_mut()
         .append_pair("series_id", &series_id.to_string());
-    let mut series =
-        get_xtream_http_data::<XtreamSeries>(url, GET_SERIES_INFO, &user_agent).await?;
+    let series = get_xtream_http_data::<XtreamSeries>(url, GET_SERIES_INFO, &client).await?;
     let mut episodes: Vec<XtreamEpisode> = series
         .episodes
         .into_values()
         .flat_map(|episode| episode)
         .collect();
-    series
-        .seasons
-        .sort_by_key(|f| get_serde_json_i64(&f.season_number));
-    let seasons: HashMap<i64, XtreamSeason> = series
+    let seasons: HashMap<i64, (i64, XtreamSeason)> = series
         .seasons
         .iter()
-        .filter_map(|x| get_serde_json_i64(&x.season_number).map(|y| (y, x.clone())))
+        .enumerate()
+        .filter_map(|(position, x)| {
+            get_serde_json_i64(&x.season_number).map(|y| (y, (position as i64, x.clone())))
+        })
         .collect();
     episodes.sort_by(|a, b| {
         get_serde_json_i64(&a.season)
@@ -329,7 +632,7 @@ pub async fn get_episodes(
 
 fn insert_episodes(
     source: &Source,
-    seasons: HashMap<i64, XtreamSeason>,
+    seasons: HashMap<i64, (i64, XtreamSeason)>,
     episodes: Vec<XtreamEpisode>,
     series_id: i64,
     default_season_image: Option<String>,
@@ -364,7 +667,7 @@ fn insert_episode(
     source: &Source,
     tx: &Transaction,
     seasons_db: &mut HashMap<i64, i64>,
-    seasons: &HashMap<i64, XtreamSeason>,
+    seasons: &HashMap<i64, (i64, XtreamSeason)>,
     series_id: i64,
     default_season_image: Option<String>,
 ) -> Result<()> {
@@ -375,8 +678,8 @@ fn insert_episode(
         None => {
             let season = seasons
                 .get(&season_number)
-                .and_then(|f| {
-                    xtream_season_to_season(f.clone(), source.id.unwrap(), series_id)
+                .and_then(|(position, f)| {
+                    xtream_season_to_season(f.clone(), source.id.unwrap(), series_id, *position)
                         .with_context(|| "Failed to convert XtreamSeason to Season")
                         .inspect_err(|e| tracing::error!("{}", e))
                         .ok()
@@ -433,7 +736,12 @@ fn get_serde_json_i64(value: &serde_json::Value) -> Option<i64> {
         .or_else(|| value.as_i64())
 }
 
-fn xtream_season_to_season(season: XtreamSeason, source_id: i64, series_id: i64) -> Result<Season> {
+fn xtream_season_to_season(
+    season: XtreamSeason,
+    source_id: i64,
+    series_id: i64,
+    position: i64,
+) -> Result<Season> {
     let season_number = get_serde_json_i64(&season.season_number).context("no season number")?;
     Ok(Season {
         season_number,
@@ -441,6 +749,7 @@ fn xtream_season_to_season(season: XtreamSeason, source_id: i64, series_id: i64)
         source_id,
         image: season.cover_tmdb.or(season.cover).or(season.overview),
         name: format!("Season {season_number}"),
+        position: Some(position),
         ..Default::default()
     })
 }
@@ -473,5 +782,69 @@ fn episode_to_channel(
         group_id: None,
         favorite: false,
         tv_archive: None,
+        tv_archive_duration: None,
+        catchup_type: None,
+        catchup_source: None,
+        channel_number: None,
+        position: None,
+        tvg_id: None,
+        duration: None,
+        language: None,
+        country: None,
+        epg_now: None,
+        epg_next: None,
     })
 }
+
+#[cfg(test)]
+mod test_xtream {
+    use crate::source_type;
+    use crate::types::Source;
+    use crate::xtream::build_catchup_url;
+
+    fn source() -> Source {
+        Source {
+            id: None,
+            name: "xtream".to_string(),
+            url: Some("http://panel.local:8080/player_api.php".to_string()),
+            url_origin: None,
+            username: Some("user".to_string()),
+            password: Some("pass".to_string()),
+            source_type: source_type::XTREAM,
+            enabled: true,
+            user_agent: None,
+            stream_user_agent: None,
+            epg_shift: None,
+            ignore_ssl: false,
+            expand_playlists: false,
+            encoding: None,
+            last_updated: None,
+        }
+    }
+
+    #[test]
+    fn test_build_catchup_url() {
+        let start = 1718452800;
+        let url = build_catchup_url(source(), 42, start, 60, Some("Europe/Berlin")).unwrap();
+        assert_eq!(
+            url.url,
+            "http://panel.local:8080/timeshift/user/pass/60/2024-06-15:14-00/42.ts"
+        );
+        assert_eq!(
+            url.fallback_url.as_deref(),
+            Some(
+                "http://panel.local:8080/streaming/timeshift.php?username=user&password=pass&stream=42&start=2024-06-15%3A14-00&duration=60"
+            )
+        );
+        let url = build_catchup_url(source(), 42, start, 60, None).unwrap();
+        assert_eq!(
+            url.url,
+            "http://panel.local:8080/timeshift/user/pass/60/2024-06-15:12-00/42.ts"
+        );
+        let url = build_catchup_url(source(), 42, start, 60, Some("Not/AZone")).unwrap();
+        assert_eq!(
+            url.url,
+            "http://panel.local:8080/timeshift/user/pass/60/2024-06-15:12-00/42.ts"
+        );
+    }
+}